@@ -70,12 +70,21 @@ pub fn ifft_with_options<F: Field>(
     zero_factor: Option<usize>,
     root_table: Option<&FftRootTable<F>>,
 ) -> PolynomialCoeffs<F> {
-    let n = poly.len();
+    let PolynomialValues { values: mut buffer } = poly;
+    ifft_dispatch(&mut buffer, zero_factor, root_table);
+    PolynomialCoeffs { coeffs: buffer }
+}
+
+fn ifft_dispatch<F: Field>(
+    buffer: &mut [F],
+    zero_factor: Option<usize>,
+    root_table: Option<&FftRootTable<F>>,
+) {
+    let n = buffer.len();
     let lg_n = log2_strict(n);
     let n_inv = F::inverse_2exp(lg_n);
 
-    let PolynomialValues { values: mut buffer } = poly;
-    fft_dispatch(&mut buffer, zero_factor, root_table);
+    fft_dispatch(buffer, zero_factor, root_table);
 
     // We reverse all values except the first, and divide each by n.
     buffer[0] *= n_inv;
@@ -87,7 +96,22 @@ pub fn ifft_with_options<F: Field>(
         buffer[i] = coeffs_i;
         buffer[j] = coeffs_j;
     }
-    PolynomialCoeffs { coeffs: buffer }
+}
+
+/// Replaces the coefficients in `values` with the evaluations of the polynomial they define over
+/// the subgroup generated by `F::primitive_root_of_unity(log2(values.len()))`, in order.
+///
+/// Panics if `values.len()` is not a power of two.
+pub fn fft_in_place<F: Field>(values: &mut [F]) {
+    fft_dispatch(values, None, None);
+}
+
+/// The inverse of [`fft_in_place`]: replaces evaluations over the subgroup of size
+/// `values.len()` with the coefficients of the interpolating polynomial.
+///
+/// Panics if `values.len()` is not a power of two.
+pub fn ifft_in_place<F: Field>(values: &mut [F]) {
+    ifft_dispatch(values, None, None);
 }
 
 /// Generic FFT implementation that works with both scalar and packed inputs.
@@ -207,10 +231,10 @@ mod tests {
 
     use plonky2_util::{log2_ceil, log2_strict};
 
-    use crate::fft::{fft, fft_with_options, ifft};
+    use crate::fft::{fft, fft_in_place, fft_with_options, ifft, ifft_in_place};
     use crate::goldilocks_field::GoldilocksField;
     use crate::polynomial::{PolynomialCoeffs, PolynomialValues};
-    use crate::types::Field;
+    use crate::types::{Field, Sample};
 
     #[test]
     fn fft_and_ifft() {
@@ -248,6 +272,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fft_and_ifft_in_place() {
+        type F = GoldilocksField;
+        for lg_n in 0..8 {
+            let coeffs = F::rand_vec(1 << lg_n);
+
+            let mut values = coeffs.clone();
+            fft_in_place(&mut values);
+            assert_eq!(values, fft(PolynomialCoeffs::new(coeffs.clone())).values);
+
+            ifft_in_place(&mut values);
+            assert_eq!(values, coeffs);
+        }
+    }
+
     fn evaluate_naive<F: Field>(coefficients: &PolynomialCoeffs<F>) -> PolynomialValues<F> {
         let degree = coefficients.len();
         let degree_padded = 1 << log2_ceil(degree);
@@ -8,6 +8,7 @@ use alloc::vec::Vec;
 
 use serde::Serialize;
 
+use crate::field::types::Field;
 use crate::fri::reduction_strategies::FriReductionStrategy;
 
 mod challenges;
@@ -27,7 +28,8 @@ pub struct FriConfig {
     /// `rate = 2^{-rate_bits}`.
     pub rate_bits: usize,
 
-    /// Height of Merkle tree caps.
+    /// Height of Merkle tree caps. Each FRI query opens paths which are `cap_height` siblings
+    /// shorter than the full tree height, in exchange for caps of `1 << cap_height` hashes.
     pub cap_height: usize,
 
     /// Number of bits used for grinding.
@@ -45,6 +47,44 @@ impl FriConfig {
         1.0 / ((1 << self.rate_bits) as f64)
     }
 
+    /// Returns a copy of this config using `proof_of_work_bits` bits of grinding.
+    pub fn with_pow_bits(mut self, proof_of_work_bits: u32) -> Self {
+        self.proof_of_work_bits = proof_of_work_bits;
+        self
+    }
+
+    /// Number of bits used for grinding.
+    pub const fn proof_of_work_bits(&self) -> u32 {
+        self.proof_of_work_bits
+    }
+
+    /// The conjectured security of FRI with this config, in bits, for polynomials of degree
+    /// `2^degree_bits` whose challenges are drawn from the field `F` (typically an extension
+    /// field). Following the ethSTARK paper, this is the smaller of the security of the queries,
+    /// including grinding, and of the field size relative to the LDE size.
+    pub fn conjectured_security_bits<F: Field>(&self, degree_bits: usize) -> f64 {
+        let query_bits =
+            (self.num_query_rounds * self.rate_bits) as f64 + self.proof_of_work_bits as f64;
+        let field_bits = F::order().bits() as f64 - (degree_bits + self.rate_bits) as f64;
+        query_bits.min(field_bits)
+    }
+
+    /// The number of query rounds needed to reach `security_bits` bits of conjectured security
+    /// with the given rate and grinding, assuming the field is large enough. Returns `usize::MAX`
+    /// if queries are needed but `rate_bits` is zero, as no number of them is then enough.
+    pub fn num_queries_for_security(
+        security_bits: usize,
+        rate_bits: usize,
+        proof_of_work_bits: u32,
+    ) -> usize {
+        let query_security_bits = security_bits.saturating_sub(proof_of_work_bits as usize);
+        match (query_security_bits, rate_bits) {
+            (0, _) => 0,
+            (_, 0) => usize::MAX,
+            _ => query_security_bits.div_ceil(rate_bits),
+        }
+    }
+
     pub fn fri_params(&self, degree_bits: usize, hiding: bool) -> FriParams {
         let reduction_arity_bits = self.reduction_strategy.reduction_arity_bits(
             degree_bits,
@@ -110,3 +150,45 @@ impl FriParams {
         1 << self.final_poly_bits()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::extension::quadratic::QuadraticExtension;
+    use crate::field::goldilocks_field::GoldilocksField;
+    use crate::plonk::circuit_data::CircuitConfig;
+
+    type FE = QuadraticExtension<GoldilocksField>;
+
+    #[test]
+    fn test_conjectured_security_bits() {
+        let fri_config = CircuitConfig::standard_recursion_config().fri_config;
+        assert_eq!(fri_config.conjectured_security_bits::<FE>(12), 100.0);
+        // For huge instances, the field size becomes the bottleneck.
+        assert!(fri_config.conjectured_security_bits::<FE>(30) < 100.0);
+
+        let fewer_queries = FriConfig {
+            num_query_rounds: 20,
+            ..fri_config
+        };
+        assert_eq!(fewer_queries.conjectured_security_bits::<FE>(12), 76.0);
+    }
+
+    #[test]
+    fn test_num_queries_for_security() {
+        let fri_config = CircuitConfig::standard_recursion_config().fri_config;
+        assert_eq!(
+            FriConfig::num_queries_for_security(
+                100,
+                fri_config.rate_bits,
+                fri_config.proof_of_work_bits
+            ),
+            fri_config.num_query_rounds
+        );
+        assert_eq!(FriConfig::num_queries_for_security(100, 3, 0), 34);
+        assert_eq!(FriConfig::num_queries_for_security(100, 1, 20), 80);
+        assert_eq!(FriConfig::num_queries_for_security(10, 1, 20), 0);
+        assert_eq!(FriConfig::num_queries_for_security(100, 0, 20), usize::MAX);
+        assert_eq!(FriConfig::num_queries_for_security(10, 0, 20), 0);
+    }
+}
@@ -13,6 +13,7 @@ use crate::hash::merkle_tree::MerkleTree;
 use crate::iop::challenger::Challenger;
 use crate::plonk::config::GenericConfig;
 use crate::plonk::plonk_common::reduce_with_powers;
+use crate::plonk::prover::ProverObserver;
 use crate::timed;
 use crate::util::reverse_index_bits_in_place;
 use crate::util::timing::TimingTree;
@@ -27,6 +28,31 @@ pub fn fri_proof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const
     challenger: &mut Challenger<F, C::Hasher>,
     fri_params: &FriParams,
     timing: &mut TimingTree,
+) -> FriProof<F, C::Hasher, D> {
+    fri_proof_with_observer::<F, C, D>(
+        initial_merkle_trees,
+        lde_polynomial_coeffs,
+        lde_polynomial_values,
+        challenger,
+        fri_params,
+        None,
+        timing,
+    )
+}
+
+/// Like `fri_proof`, but reports each folding step to `observer`.
+pub fn fri_proof_with_observer<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    initial_merkle_trees: &[&MerkleTree<F, C::Hasher>],
+    lde_polynomial_coeffs: PolynomialCoeffs<F::Extension>,
+    lde_polynomial_values: PolynomialValues<F::Extension>,
+    challenger: &mut Challenger<F, C::Hasher>,
+    fri_params: &FriParams,
+    observer: Option<&mut dyn ProverObserver>,
+    timing: &mut TimingTree,
 ) -> FriProof<F, C::Hasher, D> {
     let n = lde_polynomial_values.len();
     assert_eq!(lde_polynomial_coeffs.len(), n);
@@ -40,6 +66,7 @@ pub fn fri_proof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const
             lde_polynomial_values,
             challenger,
             fri_params,
+            observer,
         )
     );
 
@@ -72,11 +99,13 @@ fn fri_committed_trees<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>,
     mut values: PolynomialValues<F::Extension>,
     challenger: &mut Challenger<F, C::Hasher>,
     fri_params: &FriParams,
+    mut observer: Option<&mut dyn ProverObserver>,
 ) -> FriCommitedTrees<F, C, D> {
-    let mut trees = Vec::with_capacity(fri_params.reduction_arity_bits.len());
+    let num_folds = fri_params.reduction_arity_bits.len();
+    let mut trees = Vec::with_capacity(num_folds);
 
     let mut shift = F::MULTIPLICATIVE_GROUP_GENERATOR;
-    for arity_bits in &fri_params.reduction_arity_bits {
+    for (i, arity_bits) in fri_params.reduction_arity_bits.iter().enumerate() {
         let arity = 1 << arity_bits;
 
         reverse_index_bits_in_place(&mut values.values);
@@ -100,7 +129,11 @@ fn fri_committed_trees<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>,
                 .collect::<Vec<_>>(),
         );
         shift = shift.exp_u64(arity as u64);
-        values = coeffs.coset_fft(shift.into())
+        values = coeffs.coset_fft(shift.into());
+
+        if let Some(observer) = observer.as_deref_mut() {
+            observer.fri_fold(i + 1, num_folds);
+        }
     }
 
     // The coefficients being removed here should always be zero.
@@ -46,7 +46,9 @@ pub(crate) fn compute_evaluation<F: Field + Extendable<D>, const D: usize>(
     interpolate(&points, beta, &barycentric_weights)
 }
 
-pub(crate) fn fri_verify_proof_of_work<F: RichField + Extendable<D>, const D: usize>(
+/// Checks that the FRI proof-of-work response has enough leading zeros for the grinding
+/// difficulty of `config`.
+pub fn fri_verify_proof_of_work<F: RichField + Extendable<D>, const D: usize>(
     fri_pow_response: F,
     config: &FriConfig,
 ) -> Result<()> {
@@ -70,6 +72,33 @@ pub fn verify_fri_proof<
     initial_merkle_caps: &[MerkleCap<F, C::Hasher>],
     proof: &FriProof<F, C::Hasher, D>,
     params: &FriParams,
+) -> Result<()> {
+    let query_rounds = (0..params.config.num_query_rounds).collect::<Vec<_>>();
+    verify_fri_proof_queries::<F, C, D>(
+        instance,
+        openings,
+        challenges,
+        initial_merkle_caps,
+        proof,
+        params,
+        &query_rounds,
+    )
+}
+
+/// Like `verify_fri_proof`, but only checks the query rounds whose indices are in
+/// `query_rounds`. Checking a subset of the rounds gives less soundness than checking all of them.
+pub(crate) fn verify_fri_proof_queries<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    instance: &FriInstanceInfo<F, D>,
+    openings: &FriOpenings<F, D>,
+    challenges: &FriChallenges<F, D>,
+    initial_merkle_caps: &[MerkleCap<F, C::Hasher>],
+    proof: &FriProof<F, C::Hasher, D>,
+    params: &FriParams,
+    query_rounds: &[usize],
 ) -> Result<()> {
     validate_fri_proof_shape::<F, C, D>(proof, instance, params)?;
 
@@ -87,20 +116,20 @@ pub fn verify_fri_proof<
 
     let precomputed_reduced_evals =
         PrecomputedReducedOpenings::from_os_and_alpha(openings, challenges.fri_alpha);
-    for (&x_index, round_proof) in challenges
-        .fri_query_indices
-        .iter()
-        .zip(&proof.query_round_proofs)
-    {
+    for &round in query_rounds {
+        ensure!(
+            round < params.config.num_query_rounds,
+            "Query round {round} out of range."
+        );
         fri_verifier_query_round::<F, C, D>(
             instance,
             challenges,
             &precomputed_reduced_evals,
             initial_merkle_caps,
             proof,
-            x_index,
+            challenges.fri_query_indices[round],
             n,
-            round_proof,
+            &proof.query_round_proofs[round],
             params,
         )?;
     }
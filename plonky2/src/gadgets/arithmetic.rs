@@ -9,9 +9,10 @@ use core::borrow::Borrow;
 use crate::field::extension::Extendable;
 use crate::field::types::Field64;
 use crate::gates::arithmetic_base::ArithmeticGate;
+use crate::gates::dual_mul_add::DualMulAddGate;
 use crate::gates::exponentiation::ExponentiationGate;
 use crate::hash::hash_types::RichField;
-use crate::iop::generator::{GeneratedValues, SimpleGenerator};
+use crate::iop::generator::{GeneratedValues, NonzeroTestGenerator, SimpleGenerator};
 use crate::iop::target::{BoolTarget, Target};
 use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
 use crate::plonk::circuit_builder::CircuitBuilder;
@@ -164,6 +165,19 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         self.arithmetic(F::ONE, F::ONE, x, y, z)
     }
 
+    /// Computes `a * b + c * d` using a single `DualMulAddGate` slot.
+    pub fn mul_add_mul_add(&mut self, a: Target, b: Target, c: Target, d: Target) -> Target {
+        let gate = DualMulAddGate::new_from_config(&self.config);
+        let (row, i) = self.find_slot(gate, &[], &[]);
+
+        self.connect(a, Target::wire(row, DualMulAddGate::wire_ith_a(i)));
+        self.connect(b, Target::wire(row, DualMulAddGate::wire_ith_b(i)));
+        self.connect(c, Target::wire(row, DualMulAddGate::wire_ith_c(i)));
+        self.connect(d, Target::wire(row, DualMulAddGate::wire_ith_d(i)));
+
+        Target::wire(row, DualMulAddGate::wire_ith_output(i))
+    }
+
     /// Computes `x + C`.
     pub fn add_const(&mut self, x: Target, c: F) -> Target {
         let c = self.constant(c);
@@ -194,6 +208,17 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         self.arithmetic(F::ONE, F::ONE, x, one, y)
     }
 
+    /// Adds two 32-bit words, returning the low 32 bits of the sum and the carry, such that
+    /// `a + b = sum + carry * 2^32`. Both `a` and `b` are range checked to 32 bits.
+    pub fn add_u32(&mut self, a: Target, b: Target) -> (Target, BoolTarget) {
+        self.range_check(a, 32);
+        self.range_check(b, 32);
+        let a_plus_b = self.add(a, b);
+        let (sum, carry) = self.split_low_high(a_plus_b, 32, 33);
+        // `split_low_high` range checks `carry` to a single bit.
+        (sum, BoolTarget::new_unsafe(carry))
+    }
+
     /// Adds `n` `Target`s.
     pub fn add_many<T>(&mut self, terms: impl IntoIterator<Item = T>) -> Target
     where
@@ -204,6 +229,62 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             .fold(self.zero(), |acc, t| self.add(acc, *t.borrow()))
     }
 
+    /// Computes the sum of `terms` using a balanced tree of additions, so the dependency depth is
+    /// logarithmic in `terms.len()` instead of linear as with `add_many`. Both use `terms.len() - 1`
+    /// additions, which are packed into `ArithmeticGate` rows in the same way.
+    pub fn sum(&mut self, terms: &[Target]) -> Target {
+        match terms.len() {
+            0 => self.zero(),
+            1 => terms[0],
+            _ => {
+                let mut layer = terms.to_vec();
+                while layer.len() > 1 {
+                    layer = layer
+                        .chunks(2)
+                        .map(|pair| match *pair {
+                            [x, y] => self.add(x, y),
+                            [x] => x,
+                            _ => unreachable!(),
+                        })
+                        .collect();
+                }
+                layer[0]
+            }
+        }
+    }
+
+    /// Computes the inner product `sum a_i * b_i` of two vectors of the same length. Products are
+    /// computed in pairs with `mul_add_mul_add`, and the pair sums are added with a balanced tree
+    /// as in `sum`, so the dependency depth is logarithmic in the length.
+    pub fn inner_product(&mut self, a: &[Target], b: &[Target]) -> Target {
+        assert_eq!(
+            a.len(),
+            b.len(),
+            "Inner product of vectors of different lengths"
+        );
+        let pair_sums = a
+            .chunks(2)
+            .zip(b.chunks(2))
+            .map(|(a, b)| match (a, b) {
+                (&[a0, a1], &[b0, b1]) => self.mul_add_mul_add(a0, b0, a1, b1),
+                _ => self.mul(a[0], b[0]),
+            })
+            .collect::<Vec<_>>();
+        self.sum(&pair_sums)
+    }
+
+    /// Computes the random linear combination `sum terms[i] * challenge^i` with Horner's method,
+    /// using one fused `mul_add` per term after the last.
+    pub fn random_linear_combination(&mut self, terms: &[Target], challenge: Target) -> Target {
+        match terms.split_last() {
+            Some((&last, rest)) => rest
+                .iter()
+                .rev()
+                .fold(last, |acc, &t| self.mul_add(challenge, acc, t)),
+            None => self.zero(),
+        }
+    }
+
     /// Computes `x - y`.
     pub fn sub(&mut self, x: Target, y: Target) -> Target {
         let one = self.one();
@@ -227,6 +308,25 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             .fold(self.one(), |acc, t| self.mul(acc, *t.borrow()))
     }
 
+    /// Computes the running products of `factors`, returning the final product along with the
+    /// intermediate products `factors[0] * ... * factors[i]` for each `i`. The last intermediate
+    /// product is the final one. Each step is a single multiplication, whose gate both constrains
+    /// and generates it.
+    pub fn grand_product(&mut self, factors: &[Target]) -> (Target, Vec<Target>) {
+        if factors.is_empty() {
+            return (self.one(), Vec::new());
+        }
+
+        let mut partial_products = Vec::with_capacity(factors.len());
+        let mut product = factors[0];
+        partial_products.push(product);
+        for &factor in &factors[1..] {
+            product = self.mul(product, factor);
+            partial_products.push(product);
+        }
+        (product, partial_products)
+    }
+
     /// Exponentiates `base` to the power of `2^power_log`.
     pub fn exp_power_of_2(&mut self, base: Target, power_log: usize) -> Target {
         if power_log > self.num_base_arithmetic_ops_per_gate() {
@@ -320,6 +420,36 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         self.exp_from_bits(base, exp_bits)
     }
 
+    /// Exponentiates `base` to the power of a known, typically small, `exponent` using a
+    /// left-to-right square-and-multiply chain of arithmetic operations. For large exponents,
+    /// `exp_u64` may be cheaper as it uses a single `ExponentiationGate`.
+    pub fn exp_const(&mut self, base: Target, exponent: u64) -> Target {
+        if exponent == 0 {
+            return self.one();
+        }
+
+        let mut product = base;
+        for i in (0..exponent.ilog2()).rev() {
+            product = self.square(product);
+            if (exponent >> i) & 1 == 1 {
+                product = self.mul(product, base);
+            }
+        }
+        product
+    }
+
+    /// Evaluates the polynomial with the given coefficients, ordered from the constant term
+    /// upward, at `x`. This uses Horner's rule, so each coefficient costs one fused `mul_add`.
+    pub fn eval_poly(&mut self, coeffs: &[Target], x: Target) -> Target {
+        match coeffs.split_last() {
+            Some((&leading, rest)) => rest
+                .iter()
+                .rev()
+                .fold(leading, |acc, &coeff| self.mul_add(acc, x, coeff)),
+            None => self.zero(),
+        }
+    }
+
     /// Computes `x / y`. Results in an unsatisfiable instance if `y = 0`.
     pub fn div(&mut self, x: Target, y: Target) -> Target {
         let x = self.convert_to_ext(x);
@@ -327,12 +457,29 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         self.div_extension(x, y).0[0]
     }
 
+    /// Computes `x / C`, as a multiplication by `1 / C`. Panics if `C = 0`.
+    pub fn div_const(&mut self, x: Target, c: F) -> Target {
+        let c_inv = c.try_inverse().expect("Division by the constant zero");
+        self.mul_const(c_inv, x)
+    }
+
     /// Computes `1 / x`. Results in an unsatisfiable instance if `x = 0`.
     pub fn inverse(&mut self, x: Target) -> Target {
         let x_ext = self.convert_to_ext(x);
         self.inverse_extension(x_ext).0[0]
     }
 
+    /// Asserts that `x` is nonzero, by constraining `x * dummy = 1` where `dummy` is the inverse of
+    /// `x` supplied by the prover. Results in an unsatisfiable instance if `x = 0`.
+    pub fn assert_nonzero(&mut self, x: Target) {
+        let dummy = self.add_virtual_target();
+        self.add_simple_generator(NonzeroTestGenerator { to_test: x, dummy });
+
+        let product = self.mul(x, dummy);
+        let one = self.one();
+        self.connect(product, one);
+    }
+
     /// Computes the logical NOT of the provided [`BoolTarget`].
     pub fn not(&mut self, b: BoolTarget) -> BoolTarget {
         let one = self.one();
@@ -378,6 +525,21 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
 
         equal
     }
+
+    /// Asserts that `x` is one of the given `values`, by constraining
+    /// `(x - values[0]) * ... * (x - values[n - 1]) = 0`.
+    pub fn assert_one_of(&mut self, x: Target, values: &[F]) {
+        assert!(
+            !values.is_empty(),
+            "Cannot assert membership in an empty set"
+        );
+        let differences = values
+            .iter()
+            .map(|&c| self.add_const(x, -c))
+            .collect::<Vec<_>>();
+        let product = self.mul_many(differences);
+        self.assert_zero(product);
+    }
 }
 
 #[derive(Debug, Default)]
@@ -432,3 +594,310 @@ pub(crate) struct BaseArithmeticOperation<F: Field64> {
     multiplicand_1: Target,
     addend: Target,
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::polynomial::PolynomialCoeffs;
+    use crate::field::types::{Field, Sample};
+    use crate::iop::witness::PartialWitness;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    fn test_assert_one_of(x: u64, values: &[u64]) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let xt = builder.add_virtual_target();
+        let values = values
+            .iter()
+            .map(|&v| F::from_canonical_u64(v))
+            .collect::<Vec<_>>();
+        builder.assert_one_of(xt, &values);
+        pw.set_target(xt, F::from_canonical_u64(x));
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_assert_one_of_member() -> Result<()> {
+        for x in [0, 2, 5, 9] {
+            test_assert_one_of(x, &[0, 2, 5, 9])?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_one_of_non_member() {
+        test_assert_one_of(3, &[0, 2, 5, 9]).unwrap();
+    }
+
+    #[test]
+    fn test_exp_const() -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = F::rand();
+        let xt = builder.add_virtual_target();
+        pw.set_target(xt, x);
+        for exponent in [0, 1, 2, 3, 5, 7] {
+            let result = builder.exp_const(xt, exponent);
+            let expected = builder.constant(x.exp_u64(exponent));
+            builder.connect(result, expected);
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_eval_poly() -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let coeffs = F::rand_vec(6);
+        let x = F::rand();
+        let coeff_targets = builder.add_virtual_targets(coeffs.len());
+        for (&t, &c) in coeff_targets.iter().zip(&coeffs) {
+            pw.set_target(t, c);
+        }
+        let xt = builder.add_virtual_target();
+        pw.set_target(xt, x);
+
+        let result = builder.eval_poly(&coeff_targets, xt);
+        let expected = builder.constant(PolynomialCoeffs::new(coeffs).eval(x));
+        builder.connect(result, expected);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    fn test_add_u32(a: u64, b: u64) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let at = builder.add_virtual_target();
+        let bt = builder.add_virtual_target();
+        pw.set_target(at, F::from_canonical_u64(a));
+        pw.set_target(bt, F::from_canonical_u64(b));
+        let (sum, carry) = builder.add_u32(at, bt);
+        let expected_sum = builder.constant(F::from_canonical_u64((a + b) & 0xffff_ffff));
+        builder.connect(sum, expected_sum);
+        let expected_carry = builder.constant_bool(a + b > 0xffff_ffff);
+        builder.connect(carry.target, expected_carry.target);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_add_u32_carry() -> Result<()> {
+        test_add_u32(0, 0)?;
+        test_add_u32(12345, 67890)?;
+        test_add_u32(0xffff_ffff, 1)?;
+        test_add_u32(0xffff_ffff, 0xffff_ffff)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_add_u32_out_of_range() {
+        test_add_u32(1 << 32, 0).unwrap();
+    }
+
+    #[test]
+    fn test_grand_product() -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let factors = [2, 3, 5, 7, 11].map(F::from_canonical_u64);
+        let targets = builder.add_virtual_targets(factors.len());
+        for (&t, &f) in targets.iter().zip(&factors) {
+            pw.set_target(t, f);
+        }
+
+        let (product, partial_products) = builder.grand_product(&targets);
+        assert_eq!(partial_products.len(), factors.len());
+        for (&partial_product, expected) in partial_products.iter().zip([2, 6, 30, 210, 2310]) {
+            let expected = builder.constant(F::from_canonical_u64(expected));
+            builder.connect(partial_product, expected);
+        }
+        let expected = builder.constant(F::from_canonical_u64(2310));
+        builder.connect(product, expected);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    fn test_assert_nonzero(x: F) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let xt = builder.add_virtual_target();
+        builder.assert_nonzero(xt);
+        pw.set_target(xt, x);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_assert_nonzero_passes() -> Result<()> {
+        test_assert_nonzero(F::ONE)?;
+        test_assert_nonzero(F::NEG_ONE)?;
+        test_assert_nonzero(F::rand())
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_nonzero_zero() {
+        test_assert_nonzero(F::ZERO).unwrap();
+    }
+
+    fn test_div_const(x: F, c: F) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let xt = builder.add_virtual_target();
+        pw.set_target(xt, x);
+        let quotient = builder.div_const(xt, c);
+        builder.register_public_input(quotient);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        assert_eq!(proof.public_inputs, [x / c]);
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_div_const_nonzero() -> Result<()> {
+        test_div_const(F::from_canonical_u64(42), F::from_canonical_u64(6))?;
+        test_div_const(F::rand(), F::NEG_ONE)?;
+        test_div_const(F::rand(), F::rand())
+    }
+
+    #[test]
+    #[should_panic(expected = "Division by the constant zero")]
+    fn test_div_const_zero() {
+        test_div_const(F::ONE, F::ZERO).unwrap();
+    }
+
+    #[test]
+    fn test_sum() -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let values = F::rand_vec(100);
+        let targets = builder.add_virtual_targets(values.len());
+        for (&t, &v) in targets.iter().zip(&values) {
+            pw.set_target(t, v);
+        }
+
+        let tree_sum = builder.sum(&targets);
+        let chain_sum = builder.add_many(&targets);
+        builder.connect(tree_sum, chain_sum);
+        let expected = builder.constant(values.iter().copied().sum());
+        builder.connect(tree_sum, expected);
+
+        let zero = builder.zero();
+        let empty_sum = builder.sum(&[]);
+        builder.connect(empty_sum, zero);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_inner_product() -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        for len in [8, 5] {
+            let a = F::rand_vec(len);
+            let b = F::rand_vec(len);
+            let at = builder.add_virtual_targets(len);
+            let bt = builder.add_virtual_targets(len);
+            pw.set_target_arr(&at, &a);
+            pw.set_target_arr(&bt, &b);
+
+            let inner_product = builder.inner_product(&at, &bt);
+            let expected = a.iter().zip(&b).map(|(&x, &y)| x * y).sum();
+            let expected = builder.constant(expected);
+            builder.connect(inner_product, expected);
+        }
+
+        let zero = builder.zero();
+        let empty = builder.inner_product(&[], &[]);
+        builder.connect(empty, zero);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_random_linear_combination() -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let challenge = F::from_canonical_u64(7);
+        let challenge_t = builder.add_virtual_target();
+        pw.set_target(challenge_t, challenge);
+
+        for len in [0, 1, 6] {
+            let terms = (0..len)
+                .map(|i| F::from_canonical_u64(100 + i))
+                .collect::<Vec<_>>();
+            let terms_t = builder.add_virtual_targets(len as usize);
+            pw.set_target_arr(&terms_t, &terms);
+
+            let rlc = builder.random_linear_combination(&terms_t, challenge_t);
+            let expected = terms
+                .iter()
+                .zip(challenge.powers())
+                .map(|(&t, power)| t * power)
+                .sum();
+            let expected = builder.constant(expected);
+            builder.connect(rlc, expected);
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+}
@@ -586,11 +586,11 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
 /// Represents an extension arithmetic operation in the circuit. Used to memoize results.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub(crate) struct ExtensionArithmeticOperation<F: Field64 + Extendable<D>, const D: usize> {
-    const_0: F,
-    const_1: F,
-    multiplicand_0: ExtensionTarget<D>,
-    multiplicand_1: ExtensionTarget<D>,
-    addend: ExtensionTarget<D>,
+    pub(crate) const_0: F,
+    pub(crate) const_1: F,
+    pub(crate) multiplicand_0: ExtensionTarget<D>,
+    pub(crate) multiplicand_1: ExtensionTarget<D>,
+    pub(crate) addend: ExtensionTarget<D>,
 }
 
 #[cfg(test)]
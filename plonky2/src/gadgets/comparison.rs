@@ -0,0 +1,498 @@
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use crate::field::extension::Extendable;
+use crate::hash::hash_types::RichField;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator};
+use crate::iop::target::{BoolTarget, Target};
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::CommonCircuitData;
+use crate::util::log2_ceil;
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Returns a boolean which is true iff `a >= b`, where `a` and `b` are both assumed to be
+    /// smaller than `2^num_bits`. The comparison is checked by range checking `a - b` if the boolean
+    /// claims that `a >= b`, or `b - a - 1` otherwise, to `num_bits` bits.
+    pub fn is_greater_or_equal(&mut self, a: Target, b: Target, num_bits: usize) -> BoolTarget {
+        // The difference in the wrong direction wraps around to at least `p - 2^num_bits`, which
+        // must not pass the range check.
+        assert!(
+            num_bits < F::BITS - 1,
+            "num_bits={num_bits} is too large to compare field elements"
+        );
+
+        let a_ge_b = self.add_virtual_bool_target_safe();
+        self.add_simple_generator(GreaterOrEqualGenerator { a, b, a_ge_b });
+
+        // Subtracting one in the `a < b` case makes a wrong claim fail when `a == b`, where both
+        // differences would otherwise be zero.
+        let a_minus_b = self.sub(a, b);
+        let b_minus_a = self.sub(b, a);
+        let b_minus_a_minus_one = self.add_const(b_minus_a, F::NEG_ONE);
+        let diff = self.select(a_ge_b, a_minus_b, b_minus_a_minus_one);
+        self.range_check(diff, num_bits);
+
+        a_ge_b
+    }
+
+    /// Returns a boolean which is true iff `a < b`. Unlike `is_greater_or_equal`, both `a` and `b`
+    /// are range checked to `num_bits` bits, so inputs which are too large can't be proven.
+    pub fn less_than(&mut self, a: Target, b: Target, num_bits: usize) -> BoolTarget {
+        self.range_check(a, num_bits);
+        self.range_check(b, num_bits);
+        let a_ge_b = self.is_greater_or_equal(a, b, num_bits);
+        self.not(a_ge_b)
+    }
+
+    /// Returns `|a - b|`, where `a` and `b` are both assumed to be smaller than `2^num_bits`.
+    pub fn abs_diff(&mut self, a: Target, b: Target, num_bits: usize) -> Target {
+        let a_ge_b = self.is_greater_or_equal(a, b, num_bits);
+        // These are the same operations as in `is_greater_or_equal`, so the builder reuses their
+        // results.
+        let a_minus_b = self.sub(a, b);
+        let b_minus_a = self.sub(b, a);
+        self.select(a_ge_b, a_minus_b, b_minus_a)
+    }
+
+    /// Asserts that `values` is sorted in non-decreasing order, by range checking the difference of
+    /// each pair of consecutive values to `num_bits` bits. The values are assumed to be smaller than
+    /// `2^num_bits`.
+    pub fn assert_sorted(&mut self, values: &[Target], num_bits: usize) {
+        // A decreasing pair has a difference of at least `p - 2^num_bits`, which must not pass the
+        // range check.
+        assert!(
+            num_bits < F::BITS - 1,
+            "num_bits={num_bits} is too large to compare field elements"
+        );
+        for pair in values.windows(2) {
+            let diff = self.sub(pair[1], pair[0]);
+            self.range_check(diff, num_bits);
+        }
+    }
+
+    /// Builds `max_iters` copies of `body`, for loops whose number of iterations, `count`, is only
+    /// known at proving time. `body` is called with the iteration index and a boolean which is true
+    /// iff the iteration is active, i.e. the index is less than `count`; it is up to the body to
+    /// make inactive iterations have no effect, e.g. with `select`.
+    ///
+    /// `count` is constrained to be at most `max_iters`.
+    pub fn bounded_loop(
+        &mut self,
+        max_iters: usize,
+        count: Target,
+        mut body: impl FnMut(&mut Self, usize, BoolTarget),
+    ) {
+        let num_bits = log2_ceil(max_iters + 1);
+        self.range_check(count, num_bits);
+        let max = self.constant(F::from_canonical_usize(max_iters));
+        let count_in_range = self.is_greater_or_equal(max, count, num_bits);
+        self.assert_one(count_in_range.target);
+
+        for i in 0..max_iters {
+            let iters_so_far = self.constant(F::from_canonical_usize(i + 1));
+            let active = self.is_greater_or_equal(count, iters_so_far, num_bits);
+            body(self, i, active);
+        }
+    }
+
+    /// Returns the maximum of `a` and `b`, which are both assumed to be smaller than
+    /// `2^num_bits`.
+    pub fn max(&mut self, a: Target, b: Target, num_bits: usize) -> Target {
+        let a_ge_b = self.is_greater_or_equal(a, b, num_bits);
+        self.select(a_ge_b, a, b)
+    }
+
+    /// Returns the minimum of `a` and `b`, which are both assumed to be smaller than
+    /// `2^num_bits`.
+    pub fn min(&mut self, a: Target, b: Target, num_bits: usize) -> Target {
+        let a_ge_b = self.is_greater_or_equal(a, b, num_bits);
+        self.select(a_ge_b, b, a)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct GreaterOrEqualGenerator {
+    a: Target,
+    b: Target,
+    a_ge_b: BoolTarget,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for GreaterOrEqualGenerator
+{
+    fn id(&self) -> String {
+        "GreaterOrEqualGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        vec![self.a, self.b]
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let a = witness.get_target(self.a).to_canonical_u64();
+        let b = witness.get_target(self.b).to_canonical_u64();
+
+        out_buffer.set_bool_target(self.a_ge_b, a >= b);
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target(self.a)?;
+        dst.write_target(self.b)?;
+        dst.write_target_bool(self.a_ge_b)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let a = src.read_target()?;
+        let b = src.read_target()?;
+        let a_ge_b = src.read_target_bool()?;
+        Ok(Self { a, b, a_ge_b })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::types::Field;
+    use crate::iop::generator::{SimpleGeneratorAdapter, WitnessGeneratorRef};
+    use crate::iop::witness::PartialWitness;
+    use crate::plonk::circuit_data::{CircuitConfig, CircuitData};
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    fn test_max_min(a: u64, b: u64) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let at = builder.add_virtual_target();
+        let bt = builder.add_virtual_target();
+        pw.set_target(at, F::from_canonical_u64(a));
+        pw.set_target(bt, F::from_canonical_u64(b));
+
+        let max = builder.max(at, bt, 32);
+        let expected_max = builder.constant(F::from_canonical_u64(a.max(b)));
+        builder.connect(max, expected_max);
+        let min = builder.min(at, bt, 32);
+        let expected_min = builder.constant(F::from_canonical_u64(a.min(b)));
+        builder.connect(min, expected_min);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_max_min_orderings() -> Result<()> {
+        test_max_min(3, 8)?;
+        test_max_min(8, 3)?;
+        test_max_min(0, u32::MAX as u64)?;
+        test_max_min(u32::MAX as u64, 1)
+    }
+
+    #[test]
+    fn test_max_min_equal() -> Result<()> {
+        test_max_min(0, 0)?;
+        test_max_min(12345, 12345)
+    }
+
+    fn test_less_than(a: u64, b: u64) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let at = builder.add_virtual_target();
+        let bt = builder.add_virtual_target();
+        pw.set_target(at, F::from_canonical_u64(a));
+        pw.set_target(bt, F::from_canonical_u64(b));
+
+        let a_lt_b = builder.less_than(at, bt, 32);
+        let expected = builder.constant_bool(a < b);
+        builder.connect(a_lt_b.target, expected.target);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_less_than_orderings() -> Result<()> {
+        test_less_than(3, 8)?;
+        test_less_than(8, 3)?;
+        test_less_than(5, 5)?;
+        test_less_than(0, u32::MAX as u64)?;
+        test_less_than(u32::MAX as u64, 0)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_less_than_input_too_large() {
+        test_less_than(1 << 32, (1 << 32) + 1).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_less_than_equal_wrong_witness() {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.add_virtual_target();
+        pw.set_target(x, F::from_canonical_u64(5));
+        let x_lt_x = builder.less_than(x, x, 32);
+        builder.register_public_input(x_lt_x.target);
+
+        // Claiming that `x >= x` is false makes `x < x` true.
+        let data = builder.build::<C>();
+        prove_with_forced_comparisons(data, pw, |_| Some(false)).unwrap();
+    }
+
+    fn test_abs_diff(a: u64, b: u64) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let at = builder.add_virtual_target();
+        let bt = builder.add_virtual_target();
+        pw.set_target(at, F::from_canonical_u64(a));
+        pw.set_target(bt, F::from_canonical_u64(b));
+
+        let diff = builder.abs_diff(at, bt, 32);
+        let expected = builder.constant(F::from_canonical_u64(a.abs_diff(b)));
+        builder.connect(diff, expected);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_abs_diff_greater() -> Result<()> {
+        test_abs_diff(8, 3)?;
+        test_abs_diff(u32::MAX as u64, 0)
+    }
+
+    #[test]
+    fn test_abs_diff_less() -> Result<()> {
+        test_abs_diff(3, 8)?;
+        test_abs_diff(1, u32::MAX as u64)
+    }
+
+    #[test]
+    fn test_abs_diff_equal() -> Result<()> {
+        test_abs_diff(0, 0)?;
+        test_abs_diff(12345, 12345)
+    }
+
+    fn test_bounded_loop(count: usize) -> Result<()> {
+        const MAX_ITERS: usize = 8;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let values = (0..MAX_ITERS)
+            .map(|i| F::from_canonical_usize(10 + i))
+            .collect::<Vec<_>>();
+        let count_t = builder.add_virtual_target();
+        pw.set_target(count_t, F::from_canonical_usize(count));
+
+        let mut acc = builder.zero();
+        builder.bounded_loop(MAX_ITERS, count_t, |builder, i, active| {
+            let value = builder.constant(values[i]);
+            let sum = builder.add(acc, value);
+            acc = builder.select(active, sum, acc);
+        });
+        builder.register_public_input(acc);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        assert_eq!(proof.public_inputs, [values[..count].iter().copied().sum()]);
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_bounded_loop_counts() -> Result<()> {
+        test_bounded_loop(0)?;
+        test_bounded_loop(5)?;
+        test_bounded_loop(8)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bounded_loop_count_too_large() {
+        test_bounded_loop(9).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bounded_loop_last_iteration_wrong_witness() {
+        const MAX_ITERS: usize = 8;
+        const COUNT: usize = 5;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let count_t = builder.add_virtual_target();
+        pw.set_target(count_t, F::from_canonical_usize(COUNT));
+
+        let mut acc = builder.zero();
+        let mut last_active = None;
+        builder.bounded_loop(MAX_ITERS, count_t, |builder, i, active| {
+            let incremented = builder.add_const(acc, F::ONE);
+            acc = builder.select(active, incremented, acc);
+            if i == COUNT - 1 {
+                last_active = Some(active);
+            }
+        });
+        builder.register_public_input(acc);
+        let last_active = last_active.unwrap();
+
+        // Turning off the last active iteration would run the loop `COUNT - 1` times.
+        let data = builder.build::<C>();
+        prove_with_forced_comparisons(data, pw, |comparison| {
+            (comparison.a_ge_b == last_active).then_some(false)
+        })
+        .unwrap();
+    }
+
+    fn test_assert_sorted(values: &[u64]) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let targets = builder.add_virtual_targets(values.len());
+        for (&t, &v) in targets.iter().zip(values) {
+            pw.set_target(t, F::from_canonical_u64(v));
+        }
+        builder.assert_sorted(&targets, 32);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_assert_sorted_sorted() -> Result<()> {
+        test_assert_sorted(&[])?;
+        test_assert_sorted(&[7])?;
+        test_assert_sorted(&[0, 1, 1, 5, 100, u32::MAX as u64])
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_sorted_unsorted() {
+        test_assert_sorted(&[0, 1, 5, 4, 100]).unwrap();
+    }
+
+    /// Sets a comparison result to `value`, no matter the inputs. This replaces the honest
+    /// `GreaterOrEqualGenerator`, as setting its output in the witness would conflict with it.
+    #[derive(Debug, Default)]
+    struct ForcedBoolGenerator {
+        target: BoolTarget,
+        value: bool,
+    }
+
+    impl SimpleGenerator<F, D> for ForcedBoolGenerator {
+        fn id(&self) -> String {
+            "ForcedBoolGenerator".to_string()
+        }
+
+        fn dependencies(&self) -> Vec<Target> {
+            Vec::new()
+        }
+
+        fn run_once(&self, _witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+            out_buffer.set_bool_target(self.target, self.value);
+        }
+
+        fn serialize(
+            &self,
+            dst: &mut Vec<u8>,
+            _common_data: &CommonCircuitData<F, D>,
+        ) -> IoResult<()> {
+            dst.write_target_bool(self.target)?;
+            dst.write_bool(self.value)
+        }
+
+        fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+            let target = src.read_target_bool()?;
+            let value = src.read_bool()?;
+            Ok(Self { target, value })
+        }
+    }
+
+    /// Proves `data` after overriding the result of each comparison for which `force` returns a
+    /// value.
+    fn prove_with_forced_comparisons(
+        mut data: CircuitData<F, C, D>,
+        pw: PartialWitness<F>,
+        force: impl Fn(&GreaterOrEqualGenerator) -> Option<bool>,
+    ) -> Result<()> {
+        for generator in &mut data.prover_only.generators {
+            let forced = generator
+                .downcast_ref::<SimpleGeneratorAdapter<F, GreaterOrEqualGenerator, D>>()
+                .and_then(|adapter| {
+                    let comparison = adapter.inner();
+                    force(comparison).map(|value| ForcedBoolGenerator {
+                        target: comparison.a_ge_b,
+                        value,
+                    })
+                });
+            if let Some(forced) = forced {
+                *generator = WitnessGeneratorRef::new(forced.adapter());
+            }
+        }
+
+        let proof = data.prove(pw)?;
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    fn test_is_greater_or_equal_forced(a: u64, b: u64, a_ge_b: bool) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let at = builder.constant(F::from_canonical_u64(a));
+        let bt = builder.constant(F::from_canonical_u64(b));
+        builder.is_greater_or_equal(at, bt, 32);
+
+        let data = builder.build::<C>();
+        prove_with_forced_comparisons(data, pw, |_| Some(a_ge_b))
+    }
+
+    #[test]
+    fn test_is_greater_or_equal_forced_honest() -> Result<()> {
+        // Forcing the honest results must prove, or the wrong witness tests below are meaningless.
+        test_is_greater_or_equal_forced(3, 3, true)?;
+        test_is_greater_or_equal_forced(8, 3, true)?;
+        test_is_greater_or_equal_forced(3, 8, false)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_is_greater_or_equal_wrong_witness() {
+        test_is_greater_or_equal_forced(3, 8, true).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_is_greater_or_equal_equal_wrong_witness() {
+        test_is_greater_or_equal_forced(3, 3, false).unwrap();
+    }
+}
@@ -0,0 +1,80 @@
+use crate::field::extension::Extendable;
+use crate::field::types::Field;
+use crate::hash::hash_types::RichField;
+use crate::iop::target::Target;
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+/// The coefficients of a short Weierstrass curve `y^2 = x^3 + a * x + b`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct CurveParams<F: Field> {
+    pub a: F,
+    pub b: F,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Asserts that `(x, y)` lies on the given curve over the native field.
+    pub fn assert_on_curve(&mut self, x: Target, y: Target, curve: CurveParams<F>) {
+        // `x^3 + a * x + b = (x^2 + a) * x + b`.
+        let x_squared = self.square(x);
+        let x_squared_plus_a = self.add_const(x_squared, curve.a);
+        let b = self.constant(curve.b);
+        let rhs = self.mul_add(x_squared_plus_a, x, b);
+        let lhs = self.square(y);
+        self.connect(lhs, rhs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::ops::Square;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    /// Checks the point `(x, y + y_offset)` against a curve which `(x, y)` lies on.
+    fn test_assert_on_curve(y_offset: F) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = F::from_canonical_u64(123_456_789);
+        let y = F::from_canonical_u64(987_654_321);
+        let a = F::from_canonical_u64(3);
+        let curve = CurveParams {
+            a,
+            b: y.square() - x.cube() - a * x,
+        };
+
+        let xt = builder.add_virtual_target();
+        let yt = builder.add_virtual_target();
+        pw.set_target(xt, x);
+        pw.set_target(yt, y + y_offset);
+        builder.assert_on_curve(xt, yt, curve);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_assert_on_curve_valid() -> Result<()> {
+        test_assert_on_curve(F::ZERO)?;
+        // The other point with the same `x`.
+        test_assert_on_curve(-F::from_canonical_u64(2 * 987_654_321))
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_on_curve_off_curve() {
+        test_assert_on_curve(F::ONE).unwrap();
+    }
+}
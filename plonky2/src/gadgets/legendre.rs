@@ -0,0 +1,176 @@
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use crate::field::extension::Extendable;
+use crate::hash::hash_types::RichField;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator};
+use crate::iop::target::Target;
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::CommonCircuitData;
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Computes the Legendre symbol of `x`, i.e. `0` if `x = 0`, `1` if `x` is a nonzero
+    /// quadratic residue and `-1` otherwise.
+    ///
+    /// The symbol is checked using a square root witness: of `x` if the symbol is `1`, and of
+    /// `g * x` if it is `-1`, where `g` is a fixed quadratic non-residue.
+    pub fn legendre_symbol(&mut self, x: Target) -> Target {
+        let symbol = self.add_virtual_target();
+        let sqrt = self.add_virtual_target();
+        let inv = self.add_virtual_target();
+        self.add_simple_generator(LegendreSymbolGenerator {
+            x,
+            symbol,
+            sqrt,
+            inv,
+        });
+
+        // `symbol` is in `{-1, 0, 1}`.
+        let symbol_squared = self.square(symbol);
+        let symbol_cubed = self.mul(symbol_squared, symbol);
+        self.connect(symbol_cubed, symbol);
+
+        // `symbol` is zero iff `x` is zero.
+        let x_inv = self.mul(x, inv);
+        self.connect(x_inv, symbol_squared);
+        let x_times_not_symbol_squared = self.arithmetic(F::NEG_ONE, F::ONE, x, symbol_squared, x);
+        self.assert_zero(x_times_not_symbol_squared);
+
+        // `sqrt^2 = x` if `symbol = 1`, and `sqrt^2 = g * x` if `symbol = -1`.
+        let g = F::MULTIPLICATIVE_GROUP_GENERATOR;
+        let two_inv = F::TWO.inverse();
+        let factor = self.mul_const((F::ONE - g) * two_inv, symbol);
+        let factor = self.add_const(factor, (F::ONE + g) * two_inv);
+        let expected_square = self.mul(x, factor);
+        let sqrt_squared = self.square(sqrt);
+        self.connect(sqrt_squared, expected_square);
+
+        symbol
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct LegendreSymbolGenerator {
+    x: Target,
+    symbol: Target,
+    sqrt: Target,
+    inv: Target,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for LegendreSymbolGenerator
+{
+    fn id(&self) -> String {
+        "LegendreSymbolGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        vec![self.x]
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let x = witness.get_target(self.x);
+
+        let (symbol, sqrt, inv) = if x.is_zero() {
+            (F::ZERO, F::ZERO, F::ZERO)
+        } else if x.is_quadratic_residue() {
+            (F::ONE, x.sqrt().unwrap(), x.inverse())
+        } else {
+            let sqrt = (F::MULTIPLICATIVE_GROUP_GENERATOR * x).sqrt().unwrap();
+            (F::NEG_ONE, sqrt, x.inverse())
+        };
+
+        out_buffer.set_target(self.symbol, symbol);
+        out_buffer.set_target(self.sqrt, sqrt);
+        out_buffer.set_target(self.inv, inv);
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target(self.x)?;
+        dst.write_target(self.symbol)?;
+        dst.write_target(self.sqrt)?;
+        dst.write_target(self.inv)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let x = src.read_target()?;
+        let symbol = src.read_target()?;
+        let sqrt = src.read_target()?;
+        let inv = src.read_target()?;
+        Ok(Self {
+            x,
+            symbol,
+            sqrt,
+            inv,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::types::{Field, Sample};
+    use crate::iop::witness::PartialWitness;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    fn test_legendre_symbol(x: F, expected: F) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let xt = builder.add_virtual_target();
+        let symbol = builder.legendre_symbol(xt);
+        let expected = builder.constant(expected);
+        builder.connect(symbol, expected);
+        pw.set_target(xt, x);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_legendre_symbol_zero() -> Result<()> {
+        test_legendre_symbol(F::ZERO, F::ZERO)
+    }
+
+    #[test]
+    fn test_legendre_symbol_residues() -> Result<()> {
+        let r = F::rand();
+        for x in [
+            F::ONE,
+            F::from_canonical_u64(4),
+            F::from_canonical_u64(9),
+            r * r,
+        ] {
+            test_legendre_symbol(x, F::ONE)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_legendre_symbol_non_residues() -> Result<()> {
+        // 7 generates the multiplicative group of the Goldilocks field.
+        let g = F::from_canonical_u64(7);
+        let r = F::rand();
+        for x in [g, F::NEG_ONE * g, g * r * r] {
+            test_legendre_symbol(x, F::NEG_ONE)?;
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 #[cfg(not(feature = "std"))]
-use alloc::{borrow::ToOwned, vec};
+use alloc::{borrow::ToOwned, vec, vec::Vec};
 
 use crate::field::extension::Extendable;
 use crate::gates::lookup::LookupGate;
@@ -75,6 +75,21 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         looking_out
     }
 
+    /// Looks up `input` in a 4-bit S-box, returning `table[input]`. The lookup argument also
+    /// constrains `input` to be less than 16. Like all lookup table values, the outputs must fit
+    /// in 16 bits.
+    pub fn sbox4(&mut self, input: Target, table: [F; 16]) -> Target {
+        let inputs = (0..16).collect::<Vec<u16>>();
+        let outputs = table
+            .iter()
+            .map(|x| {
+                u16::try_from(x.to_canonical_u64()).expect("S-box outputs must fit in 16 bits")
+            })
+            .collect::<Vec<_>>();
+        let lut_index = self.add_lookup_table_from_table(&inputs, &outputs);
+        self.add_lookup_from_index(input, lut_index)
+    }
+
     /// We call this function at the end of circuit building right before the PI gate to add all `LookupTableGate` and `LookupGate`.
     /// It also updates `self.lookup_rows` accordingly.
     pub fn add_all_lookups(&mut self) {
@@ -0,0 +1,154 @@
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::field::extension::Extendable;
+use crate::hash::hash_types::RichField;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator};
+use crate::iop::target::Target;
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::CommonCircuitData;
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Decodes a byte message into field elements. `bytes` holds one target per message byte,
+    /// which the prover sets when generating the witness, and `field_lengths` is the layout of
+    /// the message: the `i`-th returned target is the little-endian value of the next
+    /// `field_lengths[i]` bytes.
+    ///
+    /// Each byte is range checked, and each field must be shorter than 8 bytes so that its value
+    /// fits in the field without wrapping around.
+    pub fn decode_message(&mut self, bytes: &[Target], field_lengths: &[usize]) -> Vec<Target> {
+        assert_eq!(
+            field_lengths.iter().sum::<usize>(),
+            bytes.len(),
+            "The layout doesn't cover the message"
+        );
+        assert!(
+            field_lengths.iter().all(|&len| len * 8 < F::BITS),
+            "Message fields must be shorter than the field size"
+        );
+
+        for &byte in bytes {
+            self.assert_byte(byte);
+        }
+
+        let fields = self.add_virtual_targets(field_lengths.len());
+        self.add_simple_generator(MessageDecodeGenerator {
+            bytes: bytes.to_vec(),
+            field_lengths: field_lengths.to_vec(),
+            fields: fields.clone(),
+        });
+
+        let mut start = 0;
+        for (&len, &field) in field_lengths.iter().zip(&fields) {
+            self.assert_limb_recombination(&bytes[start..start + len], 8, field);
+            start += len;
+        }
+
+        fields
+    }
+}
+
+/// Decodes a message, given as byte targets, into field elements according to a fixed layout.
+/// The layout is part of the circuit, while the message itself is part of the witness.
+#[derive(Debug, Default)]
+pub struct MessageDecodeGenerator {
+    bytes: Vec<Target>,
+    field_lengths: Vec<usize>,
+    fields: Vec<Target>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for MessageDecodeGenerator
+{
+    fn id(&self) -> String {
+        "MessageDecodeGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.bytes.clone()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let bytes = witness.get_targets(&self.bytes);
+        let base = F::from_canonical_u64(1 << 8);
+
+        let mut start = 0;
+        for (&len, &field) in self.field_lengths.iter().zip(&self.fields) {
+            let value = bytes[start..start + len]
+                .iter()
+                .rev()
+                .fold(F::ZERO, |acc, &byte| acc * base + byte);
+            out_buffer.set_target(field, value);
+            start += len;
+        }
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target_vec(&self.bytes)?;
+        dst.write_usize_vec(&self.field_lengths)?;
+        dst.write_target_vec(&self.fields)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let bytes = src.read_target_vec()?;
+        let field_lengths = src.read_usize_vec()?;
+        let fields = src.read_target_vec()?;
+        Ok(Self {
+            bytes,
+            field_lengths,
+            fields,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::types::Field;
+    use crate::iop::witness::PartialWitness;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    #[test]
+    fn test_decode_message() -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let message = [
+            0x2a, 0x78, 0x56, 0x34, 0x12, 0xef, 0xcd, 0xab, 0x89, 0x67, 0x45, 0x23,
+        ];
+        let bytes = builder.add_virtual_targets(message.len());
+        for (&t, &byte) in bytes.iter().zip(&message) {
+            pw.set_target(t, F::from_canonical_u8(byte));
+        }
+
+        let fields = builder.decode_message(&bytes, &[1, 4, 7]);
+        builder.register_public_inputs(&fields);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        assert_eq!(
+            proof.public_inputs,
+            [
+                F::from_canonical_u64(0x2a),
+                F::from_canonical_u64(0x1234_5678),
+                F::from_canonical_u64(0x23_4567_89ab_cdef),
+            ]
+        );
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+}
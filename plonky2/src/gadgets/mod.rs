@@ -4,12 +4,21 @@
 
 pub mod arithmetic;
 pub mod arithmetic_extension;
+pub mod comparison;
+pub mod curve;
 pub mod hash;
 pub mod interpolation;
+pub mod legendre;
 pub mod lookup;
+pub mod message;
+pub mod mux;
+pub mod nonnative;
 pub mod polynomial;
 pub mod random_access;
 pub mod range_check;
+pub mod secp256k1_scalar;
 pub mod select;
 pub mod split_base;
 pub mod split_join;
+pub mod sqrt;
+pub mod u256;
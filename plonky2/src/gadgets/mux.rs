@@ -0,0 +1,159 @@
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use crate::field::extension::Extendable;
+use crate::hash::hash_types::RichField;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator};
+use crate::iop::target::{BoolTarget, Target};
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::CommonCircuitData;
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Returns `inputs[index]`, using a one-hot decomposition of `index`.
+    ///
+    /// The one-hot bits are constrained to sum to one and to recombine to `index`, which forces
+    /// `index < inputs.len()`. The output is the dot product of the bits and `inputs`.
+    pub fn mux(&mut self, index: Target, inputs: &[Target]) -> Target {
+        assert!(!inputs.is_empty(), "Cannot select from an empty list");
+
+        let one_hot = (0..inputs.len())
+            .map(|_| self.add_virtual_bool_target_safe())
+            .collect::<Vec<_>>();
+        let output = self.add_virtual_target();
+        self.add_simple_generator(MuxGenerator {
+            index,
+            inputs: inputs.to_vec(),
+            one_hot: one_hot.clone(),
+            output,
+        });
+
+        let one = self.one();
+        let sum = self.add_many(one_hot.iter().map(|b| b.target));
+        self.connect(sum, one);
+
+        let mut recombined_index = self.zero();
+        let mut selected = self.zero();
+        for (i, (&b, &x)) in one_hot.iter().zip(inputs).enumerate() {
+            recombined_index =
+                self.mul_const_add(F::from_canonical_usize(i), b.target, recombined_index);
+            selected = self.mul_add(b.target, x, selected);
+        }
+        self.connect(recombined_index, index);
+        self.connect(selected, output);
+
+        output
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MuxGenerator {
+    index: Target,
+    inputs: Vec<Target>,
+    one_hot: Vec<BoolTarget>,
+    output: Target,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for MuxGenerator {
+    fn id(&self) -> String {
+        "MuxGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        let mut deps = vec![self.index];
+        deps.extend(&self.inputs);
+        deps
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let index = witness.get_target(self.index).to_canonical_u64() as usize;
+
+        // An out-of-range index yields an all-zero decomposition, which fails the constraints.
+        for (i, &b) in self.one_hot.iter().enumerate() {
+            out_buffer.set_bool_target(b, i == index);
+        }
+        let output = self
+            .inputs
+            .get(index)
+            .map_or(F::ZERO, |&x| witness.get_target(x));
+        out_buffer.set_target(self.output, output);
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target(self.index)?;
+        dst.write_target_vec(&self.inputs)?;
+        dst.write_target_bool_vec(&self.one_hot)?;
+        dst.write_target(self.output)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let index = src.read_target()?;
+        let inputs = src.read_target_vec()?;
+        let one_hot = src.read_target_bool_vec()?;
+        let output = src.read_target()?;
+        Ok(Self {
+            index,
+            inputs,
+            one_hot,
+            output,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::types::{Field, Sample};
+    use crate::iop::witness::PartialWitness;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    fn test_mux(values: &[F], index: usize) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let inputs = builder.add_virtual_targets(values.len());
+        pw.set_target_arr(&inputs, values);
+        let index_t = builder.add_virtual_target();
+        pw.set_target(index_t, F::from_canonical_usize(index));
+
+        let selected = builder.mux(index_t, &inputs);
+        let expected = builder.add_virtual_target();
+        pw.set_target(expected, values.get(index).copied().unwrap_or(F::ZERO));
+        builder.connect(selected, expected);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_mux_each_index() -> Result<()> {
+        let values = F::rand_vec(5);
+        for i in 0..values.len() {
+            test_mux(&values, i)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mux_out_of_range() {
+        let values = F::rand_vec(5);
+        test_mux(&values, values.len()).unwrap();
+    }
+}
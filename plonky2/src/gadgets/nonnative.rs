@@ -0,0 +1,346 @@
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::marker::PhantomData;
+
+use num::BigUint;
+
+use crate::field::extension::Extendable;
+use crate::field::types::{Field, PrimeField};
+use crate::hash::hash_types::RichField;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator};
+use crate::iop::target::Target;
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::CommonCircuitData;
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
+
+/// Bits in the limbs of a `NonNativeTarget`. Products of two limbs, and sums of a few dozen such
+/// products, fit in the field without wrapping around.
+pub const NONNATIVE_LIMB_BITS: usize = 16;
+
+/// The largest supported number of limbs, i.e. non-native fields of up to 1024 bits, for which
+/// column sums stay below `2^COLUMN_BITS`.
+const MAX_NUM_LIMBS: usize = 64;
+
+/// Bound on the bits of a column sum in a product of two non-native values, plus the carry from
+/// the previous column.
+const COLUMN_BITS: usize = 2 * NONNATIVE_LIMB_BITS + 8;
+
+/// An element of the non-native field `FF`, as little-endian `NONNATIVE_LIMB_BITS`-bit limbs.
+/// The value is below `2^(NONNATIVE_LIMB_BITS * num_limbs)`, but isn't necessarily reduced.
+#[derive(Clone, Debug)]
+pub struct NonNativeTarget<FF: Field> {
+    pub limbs: Vec<Target>,
+    _phantom: PhantomData<FF>,
+}
+
+impl<FF: Field> NonNativeTarget<FF> {
+    /// Number of limbs needed to hold an element of `FF`.
+    pub fn num_limbs() -> usize {
+        FF::BITS.div_ceil(NONNATIVE_LIMB_BITS)
+    }
+
+    /// Wraps limbs which are already range checked to `NONNATIVE_LIMB_BITS` bits.
+    pub fn from_limbs(limbs: Vec<Target>) -> Self {
+        assert_eq!(limbs.len(), Self::num_limbs());
+        Self {
+            limbs,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Adds a non-native target whose limbs are range checked.
+    pub fn add_virtual_nonnative_target<FF: Field>(&mut self) -> NonNativeTarget<FF> {
+        let limbs = self.add_virtual_targets(NonNativeTarget::<FF>::num_limbs());
+        for &limb in &limbs {
+            self.range_check(limb, NONNATIVE_LIMB_BITS);
+        }
+        NonNativeTarget::from_limbs(limbs)
+    }
+
+    pub fn constant_nonnative<FF: PrimeField>(&mut self, x: FF) -> NonNativeTarget<FF> {
+        let limbs = biguint_to_limbs(
+            &x.to_canonical_biguint(),
+            NonNativeTarget::<FF>::num_limbs(),
+        )
+        .into_iter()
+        .map(|limb| self.constant(F::from_canonical_usize(limb)))
+        .collect();
+        NonNativeTarget::from_limbs(limbs)
+    }
+
+    /// Multiplies two elements of a non-native field. The result is fully reduced.
+    ///
+    /// The prover supplies `q` and `r` with `a * b = q * p + r` and `r < p`, where `p` is the
+    /// order of `FF`, and both sides of the equation are compared limb by limb once normalized.
+    pub fn nonnative_mul<FF: PrimeField>(
+        &mut self,
+        a: &NonNativeTarget<FF>,
+        b: &NonNativeTarget<FF>,
+    ) -> NonNativeTarget<FF> {
+        let num_limbs = NonNativeTarget::<FF>::num_limbs();
+        assert!(
+            num_limbs <= MAX_NUM_LIMBS,
+            "Non-native fields of {} bits are not supported",
+            FF::BITS
+        );
+        let modulus = FF::order();
+
+        // The top limb of `p` is non-zero, so `a * b / p < 2^(LIMB_BITS * (num_limbs + 1))`.
+        let quotient = self.add_virtual_targets(num_limbs + 1);
+        let remainder = self.add_virtual_targets(num_limbs);
+        let complement = self.add_virtual_targets(num_limbs);
+        self.add_simple_generator(NonNativeMulGenerator {
+            a: a.limbs.clone(),
+            b: b.limbs.clone(),
+            modulus: biguint_to_limbs(&modulus, num_limbs),
+            quotient: quotient.clone(),
+            remainder: remainder.clone(),
+            complement: complement.clone(),
+        });
+        for &limb in quotient.iter().chain(&remainder).chain(&complement) {
+            self.range_check(limb, NONNATIVE_LIMB_BITS);
+        }
+
+        // `remainder + complement = p - 1` shows that `remainder < p`.
+        let modulus_minus_one = biguint_to_limbs(&(&modulus - 1u32), num_limbs);
+        let mut carry = self.zero();
+        for i in 0..num_limbs {
+            let sum = self.add(remainder[i], complement[i]);
+            let sum = self.add(sum, carry);
+            let (low, high) =
+                self.split_low_high(sum, NONNATIVE_LIMB_BITS, NONNATIVE_LIMB_BITS + 1);
+            let expected = self.constant(F::from_canonical_usize(modulus_minus_one[i]));
+            self.connect(low, expected);
+            carry = high;
+        }
+        self.assert_zero(carry);
+
+        // `a * b = quotient * p + remainder`, compared limb by limb once both sides are normalized.
+        let modulus = biguint_to_limbs(&modulus, num_limbs);
+        let product_columns = (0..2 * num_limbs - 1)
+            .map(|k| {
+                let (a_terms, b_terms): (Vec<_>, Vec<_>) = column_indices(k, num_limbs, num_limbs)
+                    .map(|(i, j)| (a.limbs[i], b.limbs[j]))
+                    .unzip();
+                self.inner_product(&a_terms, &b_terms)
+            })
+            .collect::<Vec<_>>();
+        let reduction_columns = (0..2 * num_limbs)
+            .map(|k| {
+                let init = remainder.get(k).copied().unwrap_or_else(|| self.zero());
+                column_indices(k, num_limbs + 1, num_limbs)
+                    .filter(|&(_, j)| modulus[j] != 0)
+                    .fold(init, |acc, (i, j)| {
+                        self.mul_const_add(F::from_canonical_usize(modulus[j]), quotient[i], acc)
+                    })
+            })
+            .collect::<Vec<_>>();
+        let mut product = self.normalize_columns(&product_columns);
+        let reduction = self.normalize_columns(&reduction_columns);
+        let zero = self.zero();
+        product.resize(reduction.len(), zero);
+        for (x, y) in product.into_iter().zip(reduction) {
+            self.connect(x, y);
+        }
+
+        NonNativeTarget::from_limbs(remainder)
+    }
+
+    /// Propagates carries through the column sums of a product, returning
+    /// `NONNATIVE_LIMB_BITS`-bit limbs followed by the final carry.
+    fn normalize_columns(&mut self, columns: &[Target]) -> Vec<Target> {
+        let mut limbs = Vec::with_capacity(columns.len() + 1);
+        let mut carry = self.zero();
+        for &column in columns {
+            let sum = self.add(column, carry);
+            let (low, high) = self.split_low_high(sum, NONNATIVE_LIMB_BITS, COLUMN_BITS);
+            limbs.push(low);
+            carry = high;
+        }
+        limbs.push(carry);
+        limbs
+    }
+}
+
+/// The pairs `(i, j)` of limb indices with `i + j = k`, for operands of `a_len` and `b_len` limbs.
+fn column_indices(k: usize, a_len: usize, b_len: usize) -> impl Iterator<Item = (usize, usize)> {
+    (k.saturating_sub(b_len - 1)..=k.min(a_len - 1)).map(move |i| (i, k - i))
+}
+
+/// Splits `x` into `num_limbs` limbs of `NONNATIVE_LIMB_BITS` bits.
+pub(crate) fn biguint_to_limbs(x: &BigUint, num_limbs: usize) -> Vec<usize> {
+    assert!(
+        x.bits() as usize <= num_limbs * NONNATIVE_LIMB_BITS,
+        "Value doesn't fit in {num_limbs} limbs"
+    );
+    let mut limbs = x
+        .to_u32_digits()
+        .into_iter()
+        .flat_map(|digit| [digit as usize & 0xffff, digit as usize >> 16])
+        .collect::<Vec<_>>();
+    limbs.resize(num_limbs, 0);
+    limbs
+}
+
+fn limbs_to_biguint(limbs: &[u64]) -> BigUint {
+    limbs.iter().rev().fold(BigUint::default(), |acc, &limb| {
+        (acc << NONNATIVE_LIMB_BITS) + limb
+    })
+}
+
+/// Computes the quotient and the remainder of `a * b` by `modulus`, along with
+/// `modulus - 1 - remainder`, all as `NONNATIVE_LIMB_BITS`-bit limbs.
+#[derive(Debug, Default)]
+pub struct NonNativeMulGenerator {
+    a: Vec<Target>,
+    b: Vec<Target>,
+    modulus: Vec<usize>,
+    quotient: Vec<Target>,
+    remainder: Vec<Target>,
+    complement: Vec<Target>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for NonNativeMulGenerator {
+    fn id(&self) -> String {
+        "NonNativeMulGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.a.iter().chain(&self.b).copied().collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let get_biguint = |targets: &[Target]| {
+            let limbs = witness
+                .get_targets(targets)
+                .iter()
+                .map(|x| x.to_canonical_u64())
+                .collect::<Vec<_>>();
+            limbs_to_biguint(&limbs)
+        };
+        let a = get_biguint(&self.a);
+        let b = get_biguint(&self.b);
+        let modulus = limbs_to_biguint(
+            &self
+                .modulus
+                .iter()
+                .map(|&limb| limb as u64)
+                .collect::<Vec<_>>(),
+        );
+
+        let product = a * b;
+        let quotient = &product / &modulus;
+        let remainder = &product % &modulus;
+        let complement = &modulus - 1u32 - &remainder;
+
+        for (targets, value) in [
+            (&self.quotient, quotient),
+            (&self.remainder, remainder),
+            (&self.complement, complement),
+        ] {
+            for (&t, limb) in targets.iter().zip(biguint_to_limbs(&value, targets.len())) {
+                out_buffer.set_target(t, F::from_canonical_usize(limb));
+            }
+        }
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target_vec(&self.a)?;
+        dst.write_target_vec(&self.b)?;
+        dst.write_usize_vec(&self.modulus)?;
+        dst.write_target_vec(&self.quotient)?;
+        dst.write_target_vec(&self.remainder)?;
+        dst.write_target_vec(&self.complement)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let a = src.read_target_vec()?;
+        let b = src.read_target_vec()?;
+        let modulus = src.read_usize_vec()?;
+        let quotient = src.read_target_vec()?;
+        let remainder = src.read_target_vec()?;
+        let complement = src.read_target_vec()?;
+        Ok(Self {
+            a,
+            b,
+            modulus,
+            quotient,
+            remainder,
+            complement,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::goldilocks_field::GoldilocksField;
+    use crate::field::secp256k1_base::Secp256K1Base;
+    use crate::iop::witness::PartialWitness;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    fn test_nonnative_mul<FF: PrimeField>(a: FF, b: FF) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let at = builder.add_virtual_nonnative_target::<FF>();
+        let bt = builder.add_virtual_nonnative_target::<FF>();
+        pw.set_nonnative_target(&at, a);
+        pw.set_nonnative_target(&bt, b);
+
+        let product = builder.nonnative_mul(&at, &bt);
+        let expected = builder.constant_nonnative(a * b);
+        for (&x, &y) in product.limbs.iter().zip(&expected.limbs) {
+            builder.connect(x, y);
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_nonnative_mul_secp256k1_base() -> Result<()> {
+        let a = Secp256K1Base::from_noncanonical_biguint(
+            BigUint::parse_bytes(
+                b"79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+                16,
+            )
+            .unwrap(),
+        );
+        let b = Secp256K1Base::from_noncanonical_biguint(
+            BigUint::parse_bytes(
+                b"483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+                16,
+            )
+            .unwrap(),
+        );
+        test_nonnative_mul(a, b)?;
+        test_nonnative_mul(Secp256K1Base::NEG_ONE, Secp256K1Base::NEG_ONE)?;
+        test_nonnative_mul(Secp256K1Base::ZERO, b)
+    }
+
+    #[test]
+    fn test_nonnative_mul_goldilocks() -> Result<()> {
+        test_nonnative_mul(
+            GoldilocksField::from_canonical_u64(0x1234_5678_9abc_def0),
+            GoldilocksField::NEG_ONE,
+        )
+    }
+}
@@ -16,10 +16,18 @@ use crate::util::serialization::{Buffer, IoResult, Read, Write};
 
 impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     /// Checks that `x < 2^n_log` using a `BaseSumGate`.
+    ///
+    /// `n_log` need not be a multiple of the gate's limb count: any unused high bits of the last
+    /// gate are constrained to be zero.
     pub fn range_check(&mut self, x: Target, n_log: usize) {
         self.split_le(x, n_log);
     }
 
+    /// Checks that `x` is a byte, i.e. `x < 256`, and returns its 8 little-endian bits.
+    pub fn assert_byte(&mut self, x: Target) -> Vec<BoolTarget> {
+        self.split_le(x, 8)
+    }
+
     /// Returns the first `num_low_bits` little-endian bits of `x`.
     pub fn low_bits(&mut self, x: Target, num_low_bits: usize, num_bits: usize) -> Vec<BoolTarget> {
         let mut res = self.split_le(x, num_bits);
@@ -103,3 +111,91 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for Low
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::types::Field;
+    use crate::iop::witness::PartialWitness;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    fn test_range_check(x: u64, n_log: usize) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let xt = builder.add_virtual_target();
+        builder.range_check(xt, n_log);
+        pw.set_target(xt, F::from_canonical_u64(x));
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_range_check_1_bit() -> Result<()> {
+        test_range_check(0, 1)?;
+        test_range_check(1, 1)
+    }
+
+    #[test]
+    fn test_range_check_7_bits() -> Result<()> {
+        test_range_check(0, 7)?;
+        test_range_check(93, 7)?;
+        test_range_check((1 << 7) - 1, 7)
+    }
+
+    #[test]
+    fn test_range_check_63_bits() -> Result<()> {
+        test_range_check(0, 63)?;
+        test_range_check(0x1234_5678_9abc_def0, 63)?;
+        test_range_check((1 << 63) - 1, 63)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_range_check_out_of_range() {
+        test_range_check(1 << 7, 7).unwrap();
+    }
+
+    fn test_assert_byte(x: u64) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let xt = builder.add_virtual_target();
+        let bits = builder.assert_byte(xt);
+        assert_eq!(bits.len(), 8);
+        let sum = builder.le_sum(bits.iter());
+        builder.connect(sum, xt);
+        pw.set_target(xt, F::from_canonical_u64(x));
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_assert_byte_valid() -> Result<()> {
+        test_assert_byte(0)?;
+        test_assert_byte(0x61)?;
+        test_assert_byte(255)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_byte_out_of_range() {
+        test_assert_byte(256).unwrap();
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::field::extension::Extendable;
+use crate::field::secp256k1_scalar::Secp256K1Scalar;
+use crate::gadgets::nonnative::{NonNativeTarget, NONNATIVE_LIMB_BITS};
+use crate::gadgets::u256::{U256_LIMB_BITS, U256_NUM_LIMBS};
+use crate::hash::hash_types::RichField;
+use crate::iop::target::Target;
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Multiplies two integers modulo the order of the secp256k1 scalar field.
+    ///
+    /// `a` and `b` are given as `U256_NUM_LIMBS` little-endian limbs of `U256_LIMB_BITS` bits, as
+    /// returned by `u256_to_limbs`, and may be any integers below `2^256`. The result is returned
+    /// in the same form, and is fully reduced.
+    pub fn secp256k1_scalar_mul(&mut self, a: &[Target], b: &[Target]) -> Vec<Target> {
+        assert_eq!(a.len(), U256_NUM_LIMBS);
+        assert_eq!(b.len(), U256_NUM_LIMBS);
+
+        // This also range checks the input limbs.
+        let a = self.split_u256_limbs(a);
+        let b = self.split_u256_limbs(b);
+        let product = self.nonnative_mul(&a, &b);
+
+        product
+            .limbs
+            .chunks(2)
+            .map(|pair| {
+                self.mul_const_add(
+                    F::from_canonical_u64(1 << NONNATIVE_LIMB_BITS),
+                    pair[1],
+                    pair[0],
+                )
+            })
+            .collect()
+    }
+
+    /// Splits `U256_LIMB_BITS`-bit limbs into `NONNATIVE_LIMB_BITS`-bit limbs.
+    fn split_u256_limbs(&mut self, limbs: &[Target]) -> NonNativeTarget<Secp256K1Scalar> {
+        let limbs = limbs
+            .iter()
+            .flat_map(|&limb| {
+                let (low, high) = self.split_low_high(limb, NONNATIVE_LIMB_BITS, U256_LIMB_BITS);
+                [low, high]
+            })
+            .collect();
+        NonNativeTarget::from_limbs(limbs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use num::BigUint;
+
+    use super::*;
+    use crate::field::types::{Field, PrimeField};
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    fn u32_limbs(x: &BigUint) -> Vec<u32> {
+        let mut limbs = x.to_u32_digits();
+        limbs.resize(U256_NUM_LIMBS, 0);
+        limbs
+    }
+
+    fn test_secp256k1_scalar_mul(a: BigUint, b: BigUint) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let at = builder.add_virtual_targets(U256_NUM_LIMBS);
+        let bt = builder.add_virtual_targets(U256_NUM_LIMBS);
+        for (targets, value) in [(&at, &a), (&bt, &b)] {
+            for (&t, limb) in targets.iter().zip(u32_limbs(value)) {
+                pw.set_target(t, F::from_canonical_u32(limb));
+            }
+        }
+
+        let product = builder.secp256k1_scalar_mul(&at, &bt);
+        builder.register_public_inputs(&product);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        let expected = (Secp256K1Scalar::from_noncanonical_biguint(a)
+            * Secp256K1Scalar::from_noncanonical_biguint(b))
+        .to_canonical_biguint();
+        let expected_limbs = u32_limbs(&expected)
+            .into_iter()
+            .map(F::from_canonical_u32)
+            .collect::<Vec<_>>();
+        assert_eq!(proof.public_inputs, expected_limbs);
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_secp256k1_scalar_mul_known() -> Result<()> {
+        let a = BigUint::parse_bytes(
+            b"3f5e9c0a1b2d4e6f708192a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d6e7",
+            16,
+        )
+        .unwrap();
+        let b = BigUint::parse_bytes(
+            b"a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f90",
+            16,
+        )
+        .unwrap();
+        test_secp256k1_scalar_mul(a, b)
+    }
+
+    #[test]
+    fn test_secp256k1_scalar_mul_unreduced() -> Result<()> {
+        // Inputs which are at least the group order are reduced as well.
+        let max = (BigUint::from(1u32) << 256usize) - 1u32;
+        test_secp256k1_scalar_mul(max.clone(), max)?;
+        let order_minus_one = Secp256K1Scalar::order() - 1u32;
+        test_secp256k1_scalar_mul(order_minus_one, BigUint::from(2u32))
+    }
+}
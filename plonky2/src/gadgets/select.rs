@@ -1,8 +1,20 @@
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
 use crate::field::extension::Extendable;
+use crate::field::types::Field;
 use crate::hash::hash_types::RichField;
 use crate::iop::ext_target::ExtensionTarget;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator};
 use crate::iop::target::{BoolTarget, Target};
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
 use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::CommonCircuitData;
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
 
 impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     /// Selects `x` or `y` based on `b`, i.e., this returns `if b { x } else { y }`.
@@ -34,12 +46,102 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         let tmp = self.mul_sub(b.target, y, y);
         self.mul_sub(b.target, x, tmp)
     }
+
+    /// Returns `if b { x } else { y }` for constants `x` and `y`. The result is generated by a
+    /// `SelectConstantGenerator`, and constrained by a single arithmetic operation,
+    /// `b * (x - y) + y`, with no constant targets besides one.
+    pub fn select_constant(&mut self, b: BoolTarget, x: F, y: F) -> Target {
+        let output = self.add_virtual_target();
+        self.add_simple_generator(SelectConstantGenerator {
+            selector: b,
+            output,
+            if_true: x,
+            if_false: y,
+        });
+
+        let one = self.one();
+        let selected = self.arithmetic(x - y, y, b.target, one, one);
+        self.connect(output, selected);
+        output
+    }
+
+    /// Returns the program counter of the next instruction of a CPU: `jump_dest` if `is_jump` is
+    /// true, and `pc + instruction_size` otherwise.
+    pub fn next_pc(
+        &mut self,
+        pc: Target,
+        instruction_size: Target,
+        is_jump: BoolTarget,
+        jump_dest: Target,
+    ) -> Target {
+        let sequential_pc = self.add(pc, instruction_size);
+        self.select(is_jump, jump_dest, sequential_pc)
+    }
+
+    /// Returns `(b, a)` if `swap` is true, and `(a, b)` otherwise.
+    pub fn conditional_swap(&mut self, swap: BoolTarget, a: Target, b: Target) -> (Target, Target) {
+        let first = self.select(swap, b, a);
+        let second = self.select(swap, a, b);
+        (first, second)
+    }
+}
+
+/// Sets `output` to `if_true` or `if_false` depending on `selector`. This only computes a witness
+/// value, which `select_constant` constrains.
+#[derive(Debug, Default)]
+pub struct SelectConstantGenerator<F: Field> {
+    pub selector: BoolTarget,
+    pub output: Target,
+    pub if_true: F,
+    pub if_false: F,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for SelectConstantGenerator<F>
+{
+    fn id(&self) -> String {
+        "SelectConstantGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        vec![self.selector.target]
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let value = if witness.get_bool_target(self.selector) {
+            self.if_true
+        } else {
+            self.if_false
+        };
+        out_buffer.set_target(self.output, value);
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target_bool(self.selector)?;
+        dst.write_target(self.output)?;
+        dst.write_field(self.if_true)?;
+        dst.write_field(self.if_false)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let selector = src.read_target_bool()?;
+        let output = src.read_target()?;
+        let if_true = src.read_field()?;
+        let if_false = src.read_field()?;
+        Ok(Self {
+            selector,
+            output,
+            if_true,
+            if_false,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
 
+    use super::*;
     use crate::field::types::Sample;
     use crate::iop::witness::{PartialWitness, WitnessWrite};
     use crate::plonk::circuit_builder::CircuitBuilder;
@@ -77,4 +179,106 @@ mod tests {
 
         verify(proof, &data.verifier_only, &data.common)
     }
+
+    #[test]
+    fn test_conditional_swap() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::<F>::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let (a, b) = (F::rand(), F::rand());
+        let at = builder.add_virtual_target();
+        let bt = builder.add_virtual_target();
+        let truet = builder._true();
+        let falset = builder._false();
+
+        pw.set_target(at, a);
+        pw.set_target(bt, b);
+
+        let (kept_a, kept_b) = builder.conditional_swap(falset, at, bt);
+        builder.connect(kept_a, at);
+        builder.connect(kept_b, bt);
+        let (swapped_a, swapped_b) = builder.conditional_swap(truet, at, bt);
+        builder.connect(swapped_a, bt);
+        builder.connect(swapped_b, at);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    fn test_select_constant(selector: bool) -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::<F>::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let (x, y) = (F::rand(), F::rand());
+        let expected = if selector { x } else { y };
+        let b = builder.add_virtual_bool_target_safe();
+        pw.set_bool_target(b, selector);
+
+        let selected = builder.select_constant(b, x, y);
+        builder.register_public_input(selected);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        assert_eq!(proof.public_inputs, [expected]);
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_select_constant_true() -> Result<()> {
+        test_select_constant(true)
+    }
+
+    #[test]
+    fn test_select_constant_false() -> Result<()> {
+        test_select_constant(false)
+    }
+
+    fn test_next_pc(is_jump: bool) -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::<F>::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let pc = builder.add_virtual_target();
+        let instruction_size = builder.add_virtual_target();
+        let is_jump_t = builder.add_virtual_bool_target_safe();
+        let jump_dest = builder.add_virtual_target();
+        pw.set_target(pc, F::from_canonical_u64(100));
+        pw.set_target(instruction_size, F::from_canonical_u64(3));
+        pw.set_bool_target(is_jump_t, is_jump);
+        pw.set_target(jump_dest, F::from_canonical_u64(42));
+
+        let next_pc = builder.next_pc(pc, instruction_size, is_jump_t, jump_dest);
+        builder.register_public_input(next_pc);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        let expected = if is_jump { 42 } else { 103 };
+        assert_eq!(proof.public_inputs, [F::from_canonical_u64(expected)]);
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_next_pc_sequential() -> Result<()> {
+        test_next_pc(false)
+    }
+
+    #[test]
+    fn test_next_pc_jump() -> Result<()> {
+        test_next_pc(true)
+    }
 }
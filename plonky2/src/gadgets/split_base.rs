@@ -1,5 +1,10 @@
 #[cfg(not(feature = "std"))]
-use alloc::{format, string::String, vec, vec::Vec};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
 use core::borrow::Borrow;
 
 use itertools::Itertools;
@@ -33,7 +38,11 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     }
 
     /// Takes an iterator of bits `(b_i)` and returns `sum b_i * 2^i`, i.e.,
-    /// the number with little-endian bit representation given by `bits`.
+    /// the number with little-endian bit representation given by `bits`. This is the inverse of
+    /// `split_le`.
+    ///
+    /// The bits are not range checked again, since a `BoolTarget` is already assumed to be
+    /// boolean; use `add_virtual_bool_target_safe` for bits coming from the witness.
     pub fn le_sum(&mut self, bits: impl Iterator<Item = impl Borrow<BoolTarget>>) -> Target {
         let bits = bits.map(|b| *b.borrow()).collect_vec();
         let num_bits = bits.len();
@@ -78,6 +87,28 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
 
         Target::wire(row, BaseSumGate::<2>::WIRE_SUM)
     }
+
+    /// Returns `sum limbs[i] * 2^(limb_bits * i)`, i.e. the number whose little-endian base
+    /// `2^limb_bits` representation is given by `limbs`. The limbs are assumed to be range checked
+    /// already, and the sum is computed modulo the field order.
+    pub fn recombine_limbs(&mut self, limbs: &[Target], limb_bits: usize) -> Target {
+        let value = self.add_virtual_target();
+        self.add_simple_generator(LimbRecombineGenerator {
+            limbs: limbs.to_vec(),
+            limb_bits,
+            value,
+        });
+        self.assert_limb_recombination(limbs, limb_bits, value);
+        value
+    }
+
+    /// Asserts that `value = sum limbs[i] * 2^(limb_bits * i)`.
+    pub fn assert_limb_recombination(&mut self, limbs: &[Target], limb_bits: usize, value: Target) {
+        assert!(limb_bits < 64, "limb_bits={limb_bits} is too large");
+        let base = self.constant(F::from_canonical_u64(1 << limb_bits));
+        let sum = self.eval_poly(limbs, base);
+        self.connect(sum, value);
+    }
 }
 
 #[derive(Debug, Default)]
@@ -122,6 +153,53 @@ impl<F: RichField + Extendable<D>, const B: usize, const D: usize> SimpleGenerat
     }
 }
 
+#[derive(Debug, Default)]
+pub struct LimbRecombineGenerator {
+    limbs: Vec<Target>,
+    limb_bits: usize,
+    value: Target,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for LimbRecombineGenerator
+{
+    fn id(&self) -> String {
+        "LimbRecombineGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.limbs.clone()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let base = F::from_canonical_u64(1 << self.limb_bits);
+        let value = witness
+            .get_targets(&self.limbs)
+            .into_iter()
+            .rev()
+            .fold(F::ZERO, |acc, limb| acc * base + limb);
+
+        out_buffer.set_target(self.value, value);
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target_vec(&self.limbs)?;
+        dst.write_usize(self.limb_bits)?;
+        dst.write_target(self.value)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let limbs = src.read_target_vec()?;
+        let limb_bits = src.read_usize()?;
+        let value = src.read_target()?;
+        Ok(Self {
+            limbs,
+            limb_bits,
+            value,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
@@ -197,4 +275,62 @@ mod tests {
 
         verify(proof, &data.verifier_only, &data.common)
     }
+
+    fn test_le_sum_bits(num_bits: usize) -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let n = OsRng.gen::<u64>() >> (64 - num_bits);
+        let bits = (0..num_bits)
+            .map(|i| {
+                let bit = builder.add_virtual_bool_target_safe();
+                pw.set_bool_target(bit, (n >> i) & 1 == 1);
+                bit
+            })
+            .collect::<Vec<_>>();
+        let sum = builder.le_sum(bits.iter());
+        let expected = builder.constant(F::from_canonical_u64(n));
+        builder.connect(sum, expected);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_le_sum() -> Result<()> {
+        test_le_sum_bits(8)?;
+        test_le_sum_bits(32)?;
+        test_le_sum_bits(63)
+    }
+
+    #[test]
+    fn test_recombine_limbs() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let n: u64 = 0x1234_5678_9abc_def0;
+        let limbs = builder.add_virtual_targets(4);
+        for (i, &limb) in limbs.iter().enumerate() {
+            builder.range_check(limb, 16);
+            pw.set_target(limb, F::from_canonical_u64((n >> (16 * i)) & 0xffff));
+        }
+        let value = builder.recombine_limbs(&limbs, 16);
+        let expected = builder.constant(F::from_canonical_u64(n));
+        builder.connect(value, expected);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
 }
@@ -58,6 +58,49 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
 
         bits
     }
+
+    /// Like `split_le`, but returns the bits in big-endian order, i.e. most significant first.
+    pub fn split_be(&mut self, integer: Target, num_bits: usize) -> Vec<BoolTarget> {
+        let mut bits = self.split_le(integer, num_bits);
+        bits.reverse();
+        bits
+    }
+
+    /// Rotates the `num_bits`-bit word `word` left by `rotation` bits, i.e. towards its most
+    /// significant bits. The word is range checked to `num_bits` bits, which must be small enough
+    /// for the result to fit in the field; for 64-bit words over Goldilocks, each 32-bit half
+    /// should be handled separately.
+    pub fn rotate_left(&mut self, word: Target, rotation: usize, num_bits: usize) -> Target {
+        assert!(num_bits > 0, "Can't rotate an empty word");
+        let bits = self.split_le(word, num_bits);
+        // The top `rotation` bits become the bottom ones.
+        let split = num_bits - rotation % num_bits;
+        self.le_sum(bits[split..].iter().chain(&bits[..split]))
+    }
+
+    /// Returns the number of set bits of `x`, which is range checked to `num_bits` bits.
+    pub fn popcount(&mut self, x: Target, num_bits: usize) -> Target {
+        let bits = self
+            .split_le(x, num_bits)
+            .into_iter()
+            .map(|b| b.target)
+            .collect::<Vec<_>>();
+        self.sum(&bits)
+    }
+
+    /// Asserts that `x` is a power of two below `2^num_bits`, i.e. that exactly one of its
+    /// `num_bits` bits is set, and returns the index of that bit, i.e. `log2(x)`.
+    pub fn assert_power_of_two(&mut self, x: Target, num_bits: usize) -> Target {
+        let bits = self.split_le(x, num_bits);
+        let num_set_bits = self.add_many(bits.iter().map(|b| b.target));
+        self.assert_one(num_set_bits);
+
+        // Since only one bit is set, this is its index.
+        let zero = self.zero();
+        bits.iter().enumerate().fold(zero, |acc, (i, b)| {
+            self.mul_const_add(F::from_canonical_usize(i), b.target, acc)
+        })
+    }
 }
 
 #[derive(Debug, Default)]
@@ -162,3 +205,151 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for Wir
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::types::Field;
+    use crate::iop::witness::PartialWitness;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    #[test]
+    fn test_split_be() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.add_virtual_target();
+        pw.set_target(x, F::from_canonical_u64(0b1101));
+        let be_bits = builder.split_be(x, 6);
+        let le_bits = builder.split_le(x, 6);
+
+        let expected_be = [false, false, true, true, false, true];
+        for (&bit, &expected) in be_bits.iter().zip(&expected_be) {
+            let expected = builder.constant_bool(expected);
+            builder.connect(bit.target, expected.target);
+        }
+        for (&be_bit, &le_bit) in be_bits.iter().rev().zip(&le_bits) {
+            builder.connect(be_bit.target, le_bit.target);
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_popcount() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let values = [0, 1, 0b1011, 0xff, u32::MAX as u64, 1 << 31];
+        for &x in &values {
+            let xt = builder.add_virtual_target();
+            pw.set_target(xt, F::from_canonical_u64(x));
+            let count = builder.popcount(xt, 32);
+            builder.register_public_input(count);
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        let expected = [0, 1, 3, 8, 32, 1].map(F::from_canonical_u64);
+        assert_eq!(proof.public_inputs, expected);
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    fn test_assert_power_of_two(x: u64) -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let xt = builder.add_virtual_target();
+        pw.set_target(xt, F::from_canonical_u64(x));
+        let index = builder.assert_power_of_two(xt, 32);
+        builder.register_public_input(index);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        assert_eq!(
+            proof.public_inputs,
+            [F::from_canonical_u32(x.trailing_zeros())]
+        );
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_assert_power_of_two_valid() -> Result<()> {
+        test_assert_power_of_two(1)?;
+        test_assert_power_of_two(2)?;
+        test_assert_power_of_two(64)?;
+        test_assert_power_of_two(1 << 31)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_power_of_two_zero() {
+        test_assert_power_of_two(0).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_power_of_two_three() {
+        test_assert_power_of_two(3).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_power_of_two_six() {
+        test_assert_power_of_two(6).unwrap();
+    }
+
+    fn test_rotate_left(word: u64, rotation: usize, num_bits: usize) -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.add_virtual_target();
+        pw.set_target(x, F::from_canonical_u64(word));
+        let rotated = builder.rotate_left(x, rotation, num_bits);
+        builder.register_public_input(rotated);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        let mask = (1 << num_bits) - 1;
+        let shift = rotation % num_bits;
+        let expected = ((word << shift) | (word >> (num_bits - shift))) & mask;
+        assert_eq!(proof.public_inputs, [F::from_canonical_u64(expected)]);
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_rotate_left_amounts() -> Result<()> {
+        let word = 0x8765_4321;
+        for rotation in [0, 1, 4, 13, 31, 32, 36] {
+            test_rotate_left(word, rotation, 32)?;
+        }
+        test_rotate_left(0b1_0110, 3, 5)
+    }
+}
@@ -0,0 +1,121 @@
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use crate::field::extension::Extendable;
+use crate::hash::hash_types::RichField;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator};
+use crate::iop::target::Target;
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::CommonCircuitData;
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Computes a square root of `x`, which must be a quadratic residue (or zero). Either root may
+    /// be returned; the only constraint is `sqrt^2 = x`.
+    ///
+    /// If `x` is not a quadratic residue, witness generation panics, since no valid witness
+    /// exists. Use `legendre_symbol` first if `x` may be a non-residue.
+    pub fn sqrt(&mut self, x: Target) -> Target {
+        let sqrt = self.add_virtual_target();
+        self.add_simple_generator(SqrtGenerator { x, sqrt });
+
+        let sqrt_squared = self.square(sqrt);
+        self.connect(sqrt_squared, x);
+
+        sqrt
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SqrtGenerator {
+    x: Target,
+    sqrt: Target,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for SqrtGenerator {
+    fn id(&self) -> String {
+        "SqrtGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        vec![self.x]
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let x = witness.get_target(self.x);
+        let sqrt = x
+            .sqrt()
+            .unwrap_or_else(|| panic!("{x} is not a quadratic residue"));
+
+        out_buffer.set_target(self.sqrt, sqrt);
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target(self.x)?;
+        dst.write_target(self.sqrt)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let x = src.read_target()?;
+        let sqrt = src.read_target()?;
+        Ok(Self { x, sqrt })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::types::{Field, Sample};
+    use crate::iop::witness::PartialWitness;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    fn test_sqrt(x: F) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let xt = builder.add_virtual_target();
+        builder.sqrt(xt);
+        pw.set_target(xt, x);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_sqrt_squares() -> Result<()> {
+        let r = F::rand();
+        for x in [
+            F::ZERO,
+            F::ONE,
+            F::from_canonical_u64(4),
+            F::from_canonical_u64(9),
+            r * r,
+        ] {
+            test_sqrt(x)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sqrt_non_residue() {
+        // 7 generates the multiplicative group of the Goldilocks field, so it is a non-residue.
+        test_sqrt(F::from_canonical_u64(7)).unwrap();
+    }
+}
@@ -0,0 +1,146 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::field::extension::Extendable;
+use crate::hash::hash_types::RichField;
+use crate::iop::target::Target;
+use crate::plonk::circuit_builder::CircuitBuilder;
+
+/// Number of bits in each limb returned by `u256_to_limbs`.
+pub const U256_LIMB_BITS: usize = 32;
+
+/// Number of limbs returned by `u256_to_limbs`.
+pub const U256_NUM_LIMBS: usize = 256 / U256_LIMB_BITS;
+
+impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
+    /// Decomposes a 256-bit value into `U256_NUM_LIMBS` little-endian limbs of `U256_LIMB_BITS`
+    /// bits each, which are all representable in the field.
+    ///
+    /// The value is given as little-endian limbs, either as 32 byte targets or as 8 32-bit limb
+    /// targets. Every input limb is range-checked, and each output limb is constrained to be the
+    /// recombination of the input limbs it covers.
+    pub fn u256_to_limbs(&mut self, value: &[Target]) -> Vec<Target> {
+        assert!(
+            value.len() == 32 || value.len() == U256_NUM_LIMBS,
+            "Expected 32 bytes or {} 32-bit limbs, got {} targets",
+            U256_NUM_LIMBS,
+            value.len()
+        );
+        let input_bits = 256 / value.len();
+        for &x in value {
+            self.range_check(x, input_bits);
+        }
+
+        let inputs_per_limb = U256_LIMB_BITS / input_bits;
+        let base = F::from_canonical_u64(1 << input_bits);
+        value
+            .chunks(inputs_per_limb)
+            .map(|chunk| {
+                let mut rev_chunk = chunk.iter().rev();
+                let mut limb = *rev_chunk.next().unwrap();
+                for &x in rev_chunk {
+                    limb = self.mul_const_add(base, limb, x);
+                }
+                limb
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::types::Field;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    fn test_u256_bytes(bytes: [u8; 32]) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let byte_targets = builder.add_virtual_targets(32);
+        let limbs = builder.u256_to_limbs(&byte_targets);
+        assert_eq!(limbs.len(), U256_NUM_LIMBS);
+
+        for (&t, &b) in byte_targets.iter().zip(&bytes) {
+            pw.set_target(t, F::from_canonical_u8(b));
+        }
+        for (i, &limb) in limbs.iter().enumerate() {
+            let expected = u32::from_le_bytes(bytes[4 * i..4 * i + 4].try_into().unwrap());
+            let expected = builder.constant(F::from_canonical_u32(expected));
+            builder.connect(limb, expected);
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    fn test_u256_u32_limbs(limb_values: [u32; U256_NUM_LIMBS]) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let limb_targets = builder.add_virtual_targets(U256_NUM_LIMBS);
+        let limbs = builder.u256_to_limbs(&limb_targets);
+
+        for (&t, &v) in limb_targets.iter().zip(&limb_values) {
+            pw.set_target(t, F::from_canonical_u32(v));
+        }
+        for (&limb, &v) in limbs.iter().zip(&limb_values) {
+            let expected = builder.constant(F::from_canonical_u32(v));
+            builder.connect(limb, expected);
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_u256_to_limbs_zero() -> Result<()> {
+        test_u256_bytes([0; 32])?;
+        test_u256_u32_limbs([0; U256_NUM_LIMBS])
+    }
+
+    #[test]
+    fn test_u256_to_limbs_max() -> Result<()> {
+        test_u256_bytes([u8::MAX; 32])?;
+        test_u256_u32_limbs([u32::MAX; U256_NUM_LIMBS])
+    }
+
+    #[test]
+    fn test_u256_to_limbs_mixed() -> Result<()> {
+        let bytes = core::array::from_fn(|i| (i * 37 + 11) as u8);
+        test_u256_bytes(bytes)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_u256_to_limbs_byte_out_of_range() {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let byte_targets = builder.add_virtual_targets(32);
+        builder.u256_to_limbs(&byte_targets);
+        pw.set_target(byte_targets[0], F::from_canonical_u16(256));
+        for &t in &byte_targets[1..] {
+            pw.set_target(t, F::ZERO);
+        }
+
+        let data = builder.build::<C>();
+        data.prove(pw).unwrap();
+    }
+}
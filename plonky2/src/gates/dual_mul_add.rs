@@ -0,0 +1,275 @@
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::field::extension::Extendable;
+use crate::field::packed::PackedField;
+use crate::gates::gate::Gate;
+use crate::gates::packed_util::PackedEvaluableBase;
+use crate::gates::util::StridedConstraintConsumer;
+use crate::hash::hash_types::RichField;
+use crate::iop::ext_target::ExtensionTarget;
+use crate::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGeneratorRef};
+use crate::iop::target::Target;
+use crate::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::{CircuitConfig, CommonCircuitData};
+use crate::plonk::vars::{
+    EvaluationTargets, EvaluationVars, EvaluationVarsBase, EvaluationVarsBaseBatch,
+    EvaluationVarsBasePacked,
+};
+use crate::util::serialization::{Buffer, IoResult, Read, Write};
+
+/// A gate which computes the sum of two products, i.e. `result = a.b + c.d`. If the config has
+/// enough routed wires, it can support several such operations in one gate.
+#[derive(Debug, Clone)]
+pub struct DualMulAddGate {
+    /// Number of dual multiply-add operations performed by the gate.
+    pub num_ops: usize,
+}
+
+impl DualMulAddGate {
+    pub const fn new_from_config(config: &CircuitConfig) -> Self {
+        Self {
+            num_ops: Self::num_ops(config),
+        }
+    }
+
+    /// Determine the maximum number of operations that can fit in one gate for the given config.
+    pub(crate) const fn num_ops(config: &CircuitConfig) -> usize {
+        let wires_per_op = 5;
+        config.num_routed_wires / wires_per_op
+    }
+
+    pub(crate) const fn wire_ith_a(i: usize) -> usize {
+        5 * i
+    }
+    pub(crate) const fn wire_ith_b(i: usize) -> usize {
+        5 * i + 1
+    }
+    pub(crate) const fn wire_ith_c(i: usize) -> usize {
+        5 * i + 2
+    }
+    pub(crate) const fn wire_ith_d(i: usize) -> usize {
+        5 * i + 3
+    }
+    pub(crate) const fn wire_ith_output(i: usize) -> usize {
+        5 * i + 4
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for DualMulAddGate {
+    fn id(&self) -> String {
+        format!("{self:?}")
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_usize(self.num_ops)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let num_ops = src.read_usize()?;
+        Ok(Self { num_ops })
+    }
+
+    fn eval_unfiltered(&self, vars: EvaluationVars<F, D>) -> Vec<F::Extension> {
+        let mut constraints = Vec::with_capacity(self.num_ops);
+        for i in 0..self.num_ops {
+            let a = vars.local_wires[Self::wire_ith_a(i)];
+            let b = vars.local_wires[Self::wire_ith_b(i)];
+            let c = vars.local_wires[Self::wire_ith_c(i)];
+            let d = vars.local_wires[Self::wire_ith_d(i)];
+            let output = vars.local_wires[Self::wire_ith_output(i)];
+            let computed_output = a * b + c * d;
+
+            constraints.push(output - computed_output);
+        }
+
+        constraints
+    }
+
+    fn eval_unfiltered_base_one(
+        &self,
+        _vars: EvaluationVarsBase<F>,
+        _yield_constr: StridedConstraintConsumer<F>,
+    ) {
+        panic!("use eval_unfiltered_base_packed instead");
+    }
+
+    fn eval_unfiltered_base_batch(&self, vars_base: EvaluationVarsBaseBatch<F>) -> Vec<F> {
+        self.eval_unfiltered_base_batch_packed(vars_base)
+    }
+
+    fn eval_unfiltered_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: EvaluationTargets<D>,
+    ) -> Vec<ExtensionTarget<D>> {
+        let mut constraints = Vec::with_capacity(self.num_ops);
+        for i in 0..self.num_ops {
+            let a = vars.local_wires[Self::wire_ith_a(i)];
+            let b = vars.local_wires[Self::wire_ith_b(i)];
+            let c = vars.local_wires[Self::wire_ith_c(i)];
+            let d = vars.local_wires[Self::wire_ith_d(i)];
+            let output = vars.local_wires[Self::wire_ith_output(i)];
+            let computed_output = {
+                let cd = builder.mul_extension(c, d);
+                builder.mul_add_extension(a, b, cd)
+            };
+
+            let diff = builder.sub_extension(output, computed_output);
+            constraints.push(diff);
+        }
+
+        constraints
+    }
+
+    fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<WitnessGeneratorRef<F, D>> {
+        (0..self.num_ops)
+            .map(|i| WitnessGeneratorRef::new(DualMulAddGenerator { row, i }.adapter()))
+            .collect()
+    }
+
+    fn num_wires(&self) -> usize {
+        self.num_ops * 5
+    }
+
+    fn num_constants(&self) -> usize {
+        0
+    }
+
+    fn degree(&self) -> usize {
+        2
+    }
+
+    fn num_constraints(&self) -> usize {
+        self.num_ops
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> PackedEvaluableBase<F, D> for DualMulAddGate {
+    fn eval_unfiltered_base_packed<P: PackedField<Scalar = F>>(
+        &self,
+        vars: EvaluationVarsBasePacked<P>,
+        mut yield_constr: StridedConstraintConsumer<P>,
+    ) {
+        for i in 0..self.num_ops {
+            let a = vars.local_wires[Self::wire_ith_a(i)];
+            let b = vars.local_wires[Self::wire_ith_b(i)];
+            let c = vars.local_wires[Self::wire_ith_c(i)];
+            let d = vars.local_wires[Self::wire_ith_d(i)];
+            let output = vars.local_wires[Self::wire_ith_output(i)];
+            let computed_output = a * b + c * d;
+
+            yield_constr.one(output - computed_output);
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DualMulAddGenerator {
+    row: usize,
+    i: usize,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for DualMulAddGenerator {
+    fn id(&self) -> String {
+        "DualMulAddGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        [
+            DualMulAddGate::wire_ith_a(self.i),
+            DualMulAddGate::wire_ith_b(self.i),
+            DualMulAddGate::wire_ith_c(self.i),
+            DualMulAddGate::wire_ith_d(self.i),
+        ]
+        .iter()
+        .map(|&i| Target::wire(self.row, i))
+        .collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let get_wire = |wire: usize| -> F { witness.get_target(Target::wire(self.row, wire)) };
+
+        let a = get_wire(DualMulAddGate::wire_ith_a(self.i));
+        let b = get_wire(DualMulAddGate::wire_ith_b(self.i));
+        let c = get_wire(DualMulAddGate::wire_ith_c(self.i));
+        let d = get_wire(DualMulAddGate::wire_ith_d(self.i));
+
+        let output_target = Target::wire(self.row, DualMulAddGate::wire_ith_output(self.i));
+
+        out_buffer.set_target(output_target, a * b + c * d)
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_usize(self.row)?;
+        dst.write_usize(self.i)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let row = src.read_usize()?;
+        let i = src.read_usize()?;
+        Ok(Self { row, i })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::field::goldilocks_field::GoldilocksField;
+    use crate::field::types::Sample;
+    use crate::gates::dual_mul_add::DualMulAddGate;
+    use crate::gates::gate_testing::{test_eval_fns, test_low_degree};
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    #[test]
+    fn low_degree() {
+        let gate = DualMulAddGate::new_from_config(&CircuitConfig::standard_recursion_config());
+        test_low_degree::<GoldilocksField, _, 4>(gate);
+    }
+
+    #[test]
+    fn eval_fns() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let gate = DualMulAddGate::new_from_config(&CircuitConfig::standard_recursion_config());
+        test_eval_fns::<F, C, _, D>(gate)
+    }
+
+    #[test]
+    fn mul_add_mul_add_matches_naive() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let [a, b, c, d] = builder.add_virtual_target_arr::<4>();
+        let fused = builder.mul_add_mul_add(a, b, c, d);
+        let ab = builder.mul(a, b);
+        let cd = builder.mul(c, d);
+        let naive = builder.add(ab, cd);
+        builder.connect(fused, naive);
+
+        let mut pw = PartialWitness::new();
+        for t in [a, b, c, d] {
+            pw.set_target(t, F::rand());
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+}
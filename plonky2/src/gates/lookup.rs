@@ -192,8 +192,11 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for Loo
         let get_wire = |wire: usize| -> F { witness.get_target(Target::wire(self.row, wire)) };
 
         let input_val = get_wire(LookupGate::wire_ith_looking_inp(self.slot_nb));
-        let (input, output) = self.lut[input_val.to_canonical_u64() as usize];
-        if input_val == F::from_canonical_u16(input) {
+        // Tables are usually indexed by their inputs, so try the entry at that index first.
+        let entry = self.lut.get(input_val.to_canonical_u64() as usize).copied();
+        if let Some((_, output)) =
+            entry.filter(|&(input, _)| input_val == F::from_canonical_u16(input))
+        {
             let output_val = F::from_canonical_u16(output);
 
             let out_wire = Target::wire(self.row, LookupGate::wire_ith_looking_out(self.slot_nb));
@@ -28,6 +28,7 @@ pub mod arithmetic_extension;
 pub mod base_sum;
 pub mod constant;
 pub mod coset_interpolation;
+pub mod dual_mul_add;
 pub mod exponentiation;
 pub mod gate;
 pub mod lookup;
@@ -6,7 +6,8 @@ use core::fmt::Debug;
 use crate::field::extension::Extendable;
 use crate::field::types::Field;
 use crate::hash::hash_types::{HashOut, HashOutTarget, RichField, NUM_HASH_OUT_ELTS};
-use crate::iop::target::Target;
+use crate::hash::poseidon::PoseidonPermutation;
+use crate::iop::target::{BoolTarget, Target};
 use crate::plonk::circuit_builder::CircuitBuilder;
 use crate::plonk::config::AlgebraicHasher;
 
@@ -27,6 +28,30 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         HashOutTarget::from_vec(self.hash_n_to_m_no_pad::<H>(inputs, NUM_HASH_OUT_ELTS))
     }
 
+    /// Hashes a sequence of elements by absorbing them into a sponge, `RATE` elements at a time,
+    /// and returns the final digest. This matches `H::hash_no_pad` out of circuit.
+    pub fn hash_sequence<H: AlgebraicHasher<F>>(&mut self, elements: &[Target]) -> HashOutTarget {
+        self.hash_n_to_hash_no_pad::<H>(elements.to_vec())
+    }
+
+    /// Returns the hash of `state` followed by `value` if `selector` is true, and `state` otherwise.
+    /// This folds values into a running hash chain only when they are selected.
+    pub fn conditional_hash_update<H: AlgebraicHasher<F>>(
+        &mut self,
+        state: HashOutTarget,
+        value: Target,
+        selector: BoolTarget,
+    ) -> HashOutTarget {
+        let mut inputs = state.elements.to_vec();
+        inputs.push(value);
+        let updated = self.hash_n_to_hash_no_pad::<H>(inputs);
+        HashOutTarget::from_vec(
+            (0..NUM_HASH_OUT_ELTS)
+                .map(|i| self.select(selector, updated.elements[i], state.elements[i]))
+                .collect(),
+        )
+    }
+
     pub fn hash_n_to_m_no_pad<H: AlgebraicHasher<F>>(
         &mut self,
         inputs: Vec<Target>,
@@ -143,3 +168,126 @@ pub fn hash_n_to_m_no_pad<F: RichField, P: PlonkyPermutation<F>>(
 pub fn hash_n_to_hash_no_pad<F: RichField, P: PlonkyPermutation<F>>(inputs: &[F]) -> HashOut<F> {
     HashOut::from_vec(hash_n_to_m_no_pad::<F, P>(inputs, NUM_HASH_OUT_ELTS))
 }
+
+/// The number of bytes encoded in each field element by `hash_bytes_to_field`. Any 7 bytes are
+/// below `2^56`, so they fit in the 64-bit fields used for hashing.
+pub const HASH_BYTES_PER_ELEMENT: usize = 7;
+
+/// Hashes a byte string with Poseidon, returning `NUM_HASH_OUT_ELTS` field elements.
+///
+/// The bytes are encoded as their number, followed by chunks of `HASH_BYTES_PER_ELEMENT` bytes,
+/// each read as a little-endian integer, with the last chunk zero-padded. Thanks to the length
+/// prefix, the encoding is injective even with padding, and encodings of different lengths differ
+/// in their first element, so hashing them without a padding rule is safe.
+pub fn hash_bytes_to_field<F: RichField>(bytes: &[u8]) -> Vec<F> {
+    let inputs = core::iter::once(F::from_canonical_usize(bytes.len()))
+        .chain(bytes.chunks(HASH_BYTES_PER_ELEMENT).map(|chunk| {
+            let mut buf = [0; 8];
+            buf[..chunk.len()].copy_from_slice(chunk);
+            F::from_canonical_u64(u64::from_le_bytes(buf))
+        }))
+        .collect::<Vec<_>>();
+    hash_n_to_m_no_pad::<F, PoseidonPermutation<F>>(&inputs, NUM_HASH_OUT_ELTS)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::types::Sample;
+    use crate::hash::poseidon::PoseidonHash;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::{GenericConfig, Hasher, PoseidonGoldilocksConfig};
+    use crate::plonk::verifier::verify;
+
+    #[test]
+    fn test_hash_bytes_to_field() {
+        type F = <PoseidonGoldilocksConfig as GenericConfig<2>>::F;
+
+        // "plonky2 hash" is 12 bytes, which are encoded in two elements.
+        let encoded = [
+            F::from_canonical_u64(12),
+            F::from_canonical_u64(u64::from_le_bytes(*b"plonky2\0")),
+            F::from_canonical_u64(u64::from_le_bytes(*b" hash\0\0\0")),
+        ];
+        let hash = hash_bytes_to_field::<F>(b"plonky2 hash");
+        assert_eq!(hash, PoseidonHash::hash_no_pad(&encoded).elements);
+        assert_eq!(hash, hash_bytes_to_field::<F>(b"plonky2 hash"));
+
+        // Trailing zeros aren't lost in the padding.
+        let messages: [&[u8]; 5] = [b"", b"\0", b"\0\0", b"abcdefg", b"abcdefg\0"];
+        for (i, a) in messages.iter().enumerate() {
+            for b in &messages[i + 1..] {
+                assert_ne!(hash_bytes_to_field::<F>(a), hash_bytes_to_field::<F>(b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_hash_sequence() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type H = <C as GenericConfig<D>>::InnerHasher;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let elements = F::rand_vec(10);
+        let elements_t = builder.add_virtual_targets(elements.len());
+        pw.set_target_arr(&elements_t, &elements);
+
+        let hash = builder.hash_sequence::<H>(&elements_t);
+        let expected = builder.add_virtual_hash();
+        pw.set_hash_target(expected, H::hash_no_pad(&elements));
+        builder.connect_hashes(hash, expected);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_conditional_hash_update() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type H = <C as GenericConfig<D>>::InnerHasher;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let values = F::rand_vec(6);
+        let selectors = [true, false, false, true, true, false];
+
+        let mut expected = HashOut::ZERO;
+        let mut state = builder.constant_hash(expected);
+        for (&value, &selected) in values.iter().zip(&selectors) {
+            if selected {
+                let mut inputs = expected.elements.to_vec();
+                inputs.push(value);
+                expected = H::hash_no_pad(&inputs);
+            }
+
+            let value_t = builder.add_virtual_target();
+            let selector_t = builder.add_virtual_bool_target_safe();
+            pw.set_target(value_t, value);
+            pw.set_bool_target(selector_t, selected);
+            state = builder.conditional_hash_update::<H>(state, value_t, selector_t);
+        }
+
+        let expected_t = builder.add_virtual_hash();
+        pw.set_hash_target(expected_t, expected);
+        builder.connect_hashes(state, expected_t);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+}
@@ -2,6 +2,7 @@
 use alloc::{vec, vec::Vec};
 
 use anyhow::{ensure, Result};
+use hashbrown::HashMap;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
@@ -126,20 +127,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         debug_assert_eq!(state.elements.len(), NUM_HASH_OUT_ELTS);
 
         for (&bit, &sibling) in leaf_index_bits.iter().zip(&proof.siblings) {
-            debug_assert_eq!(sibling.elements.len(), NUM_HASH_OUT_ELTS);
-
-            let mut perm_inputs = H::AlgebraicPermutation::default();
-            perm_inputs.set_from_slice(&state.elements, 0);
-            perm_inputs.set_from_slice(&sibling.elements, NUM_HASH_OUT_ELTS);
-            // Ensure the rest of the state, if any, is zero:
-            perm_inputs.set_from_iter(core::iter::repeat(zero), 2 * NUM_HASH_OUT_ELTS);
-            let perm_outs = self.permute_swapped::<H>(perm_inputs, bit);
-            let hash_outs = perm_outs.squeeze()[0..NUM_HASH_OUT_ELTS]
-                .try_into()
-                .unwrap();
-            state = HashOutTarget {
-                elements: hash_outs,
-            };
+            state = self.merkle_compress::<H>(state, sibling, bit, zero);
         }
 
         for i in 0..NUM_HASH_OUT_ELTS {
@@ -151,6 +139,83 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         }
     }
 
+    /// Verifies several Merkle proofs against the same cap, as with `verify_merkle_proof_to_cap`.
+    ///
+    /// Leaf hashes, compressions and cap lookups are shared between proofs whenever their inputs
+    /// are the same targets, e.g. when openings share a path prefix or are repeated, so that each
+    /// distinct hash is only computed once.
+    pub fn verify_merkle_proofs_to_cap<H: AlgebraicHasher<F>>(
+        &mut self,
+        leaves: Vec<Vec<Target>>,
+        leaf_index_bits: &[Vec<BoolTarget>],
+        merkle_cap: &MerkleCapTarget,
+        proofs: &[MerkleProofTarget],
+    ) {
+        debug_assert!(H::AlgebraicPermutation::RATE >= NUM_HASH_OUT_ELTS);
+        assert_eq!(leaves.len(), proofs.len());
+        assert_eq!(leaf_index_bits.len(), proofs.len());
+
+        let zero = self.zero();
+        let mut leaf_hashes = HashMap::new();
+        let mut compressions = HashMap::new();
+        let mut cap_entries = HashMap::new();
+
+        for ((leaf_data, index_bits), proof) in leaves.into_iter().zip(leaf_index_bits).zip(proofs)
+        {
+            let mut state = *leaf_hashes
+                .entry(leaf_data.clone())
+                .or_insert_with(|| self.hash_or_noop::<H>(leaf_data));
+
+            for (&bit, &sibling) in index_bits.iter().zip(&proof.siblings) {
+                let key = (state.elements, sibling.elements, bit.target);
+                state = *compressions
+                    .entry(key)
+                    .or_insert_with(|| self.merkle_compress::<H>(state, sibling, bit, zero));
+            }
+
+            let cap_index_bits = index_bits[proof.siblings.len()..]
+                .iter()
+                .map(|b| b.target)
+                .collect::<Vec<_>>();
+            let cap_entry = *cap_entries.entry(cap_index_bits).or_insert_with(|| {
+                let cap_index = self.le_sum(index_bits[proof.siblings.len()..].iter().copied());
+                let elements = core::array::from_fn(|i| {
+                    self.random_access(
+                        cap_index,
+                        merkle_cap.0.iter().map(|h| h.elements[i]).collect(),
+                    )
+                });
+                HashOutTarget { elements }
+            });
+            self.connect_hashes(cap_entry, state);
+        }
+    }
+
+    /// Hashes `state` with a Merkle `sibling`, with `bit` indicating whether `state` is the right
+    /// child.
+    fn merkle_compress<H: AlgebraicHasher<F>>(
+        &mut self,
+        state: HashOutTarget,
+        sibling: HashOutTarget,
+        bit: BoolTarget,
+        zero: Target,
+    ) -> HashOutTarget {
+        debug_assert_eq!(sibling.elements.len(), NUM_HASH_OUT_ELTS);
+
+        let mut perm_inputs = H::AlgebraicPermutation::default();
+        perm_inputs.set_from_slice(&state.elements, 0);
+        perm_inputs.set_from_slice(&sibling.elements, NUM_HASH_OUT_ELTS);
+        // Ensure the rest of the state, if any, is zero:
+        perm_inputs.set_from_iter(core::iter::repeat(zero), 2 * NUM_HASH_OUT_ELTS);
+        let perm_outs = self.permute_swapped::<H>(perm_inputs, bit);
+        let hash_outs = perm_outs.squeeze()[0..NUM_HASH_OUT_ELTS]
+            .try_into()
+            .unwrap();
+        HashOutTarget {
+            elements: hash_outs,
+        }
+    }
+
     pub fn connect_hashes(&mut self, x: HashOutTarget, y: HashOutTarget) {
         for i in 0..NUM_HASH_OUT_ELTS {
             self.connect(x.elements[i], y.elements[i]);
@@ -230,4 +295,106 @@ mod tests {
 
         verify(proof, &data.verifier_only, &data.common)
     }
+
+    #[test]
+    fn test_verify_merkle_proofs_to_cap() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let log_n = 8;
+        let n = 1 << log_n;
+        let cap_height = 2;
+        let leaves = random_data::<F>(n, 7);
+        let tree = MerkleTree::<F, <C as GenericConfig<D>>::Hasher>::new(leaves, cap_height);
+
+        let cap_t = builder.add_virtual_cap(cap_height);
+        pw.set_cap_target(&cap_t, &tree.cap);
+
+        let mut add_opening = |builder: &mut CircuitBuilder<F, D>, i: usize| {
+            let proof = tree.prove(i);
+            let proof_t = MerkleProofTarget {
+                siblings: builder.add_virtual_hashes(proof.siblings.len()),
+            };
+            for (&t, &h) in proof_t.siblings.iter().zip(&proof.siblings) {
+                pw.set_hash_target(t, h);
+            }
+            let i_c = builder.constant(F::from_canonical_usize(i));
+            let i_bits = builder.split_le(i_c, log_n);
+            let data = builder.add_virtual_targets(tree.leaves[i].len());
+            for (&t, &x) in data.iter().zip(&tree.leaves[i]) {
+                pw.set_target(t, x);
+            }
+            (data, i_bits, proof_t)
+        };
+
+        let (mut leaves_t, mut bits_t, mut proofs_t) = (Vec::new(), Vec::new(), Vec::new());
+        for _ in 0..6 {
+            let (data, bits, proof) = add_opening(&mut builder, OsRng.gen_range(0..n));
+            leaves_t.push(data);
+            bits_t.push(bits);
+            proofs_t.push(proof);
+        }
+        // Repeated openings share all of their hashing.
+        leaves_t.push(leaves_t[0].clone());
+        bits_t.push(bits_t[0].clone());
+        proofs_t.push(proofs_t[0].clone());
+
+        builder.verify_merkle_proofs_to_cap::<<C as GenericConfig<D>>::InnerHasher>(
+            leaves_t, &bits_t, &cap_t, &proofs_t,
+        );
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_merkle_proofs_with_different_cap_heights() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let log_n = 8;
+        let n = 1 << log_n;
+        for cap_height in [0, 4] {
+            let leaves = random_data::<F>(n, 7);
+            let tree = MerkleTree::<F, <C as GenericConfig<D>>::Hasher>::new(leaves, cap_height);
+            let i: usize = OsRng.gen_range(0..n);
+            let proof = tree.prove(i);
+
+            let proof_t = builder.add_virtual_merkle_proof_to_cap(log_n, cap_height);
+            assert_eq!(proof_t.siblings.len(), proof.siblings.len());
+            for (&t, &h) in proof_t.siblings.iter().zip(&proof.siblings) {
+                pw.set_hash_target(t, h);
+            }
+
+            let cap_t = builder.add_virtual_cap(cap_height);
+            pw.set_cap_target(&cap_t, &tree.cap);
+
+            let i_c = builder.constant(F::from_canonical_usize(i));
+            let i_bits = builder.split_le(i_c, log_n);
+
+            let data = builder.add_virtual_targets(tree.leaves[i].len());
+            for (&t, &x) in data.iter().zip(&tree.leaves[i]) {
+                pw.set_target(t, x);
+            }
+
+            builder.verify_merkle_proof_to_cap::<<C as GenericConfig<D>>::InnerHasher>(
+                data, &i_bits, &cap_t, &proof_t,
+            );
+        }
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
 }
@@ -17,6 +17,15 @@ pub struct Challenger<F: RichField, H: Hasher<F>> {
     pub(crate) sponge_state: H::Permutation,
     pub(crate) input_buffer: Vec<F>,
     output_buffer: Vec<F>,
+    /// Every observed element and generated challenge, in order, if logging is enabled.
+    transcript_log: Option<Vec<TranscriptEntry<F>>>,
+}
+
+/// An event recorded in a `Challenger`'s transcript log.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TranscriptEntry<F> {
+    Observed(F),
+    Challenge(F),
 }
 
 /// Observes prover messages, and generates verifier challenges based on the transcript.
@@ -33,13 +42,44 @@ impl<F: RichField, H: Hasher<F>> Challenger<F, H> {
             sponge_state: H::Permutation::new(core::iter::repeat(F::ZERO)),
             input_buffer: Vec::with_capacity(H::Permutation::RATE),
             output_buffer: Vec::with_capacity(H::Permutation::RATE),
+            transcript_log: None,
         }
     }
 
+    /// Starts recording every observed element and generated challenge, e.g. so that the
+    /// Fiat-Shamir transcript of a proof can be audited.
+    pub fn enable_transcript_log(&mut self) {
+        self.transcript_log.get_or_insert_with(Vec::new);
+    }
+
+    /// Returns the entries recorded since `enable_transcript_log` was called, if it was.
+    pub fn transcript_log(&self) -> Option<&[TranscriptEntry<F>]> {
+        self.transcript_log.as_deref()
+    }
+
+    /// Creates a challenger whose transcript is bound to `domain`, so that transcripts of
+    /// independent protocols can't be confused even if they use the same permutation and absorb
+    /// the same data.
+    ///
+    /// The tag is written to the capacity part of the initial sponge state rather than absorbed
+    /// as a regular element. This way, no sequence of observed elements can make another
+    /// challenger reach the same state, whereas an absorbed tag could be mimicked by a transcript
+    /// which happens to start with the same element. Domain `0` gives the same challenger as `new`.
+    pub fn with_domain(domain: u64) -> Challenger<F, H> {
+        let mut challenger = Self::new();
+        challenger
+            .sponge_state
+            .set_elt(F::from_canonical_u64(domain), H::Permutation::WIDTH - 1);
+        challenger
+    }
+
     pub fn observe_element(&mut self, element: F) {
         // Any buffered outputs are now invalid, since they wouldn't reflect this input.
         self.output_buffer.clear();
 
+        if let Some(log) = &mut self.transcript_log {
+            log.push(TranscriptEntry::Observed(element));
+        }
         self.input_buffer.push(element);
 
         if self.input_buffer.len() == H::Permutation::RATE {
@@ -86,9 +126,14 @@ impl<F: RichField, H: Hasher<F>> Challenger<F, H> {
             self.duplexing();
         }
 
-        self.output_buffer
+        let challenge = self
+            .output_buffer
             .pop()
-            .expect("Output buffer should be non-empty")
+            .expect("Output buffer should be non-empty");
+        if let Some(log) = &mut self.transcript_log {
+            log.push(TranscriptEntry::Challenge(challenge));
+        }
+        challenge
     }
 
     pub fn get_n_challenges(&mut self, n: usize) -> Vec<F> {
@@ -297,8 +342,8 @@ mod tests {
     #[cfg(not(feature = "std"))]
     use alloc::vec::Vec;
 
-    use crate::field::types::Sample;
-    use crate::iop::challenger::{Challenger, RecursiveChallenger};
+    use crate::field::types::{Field, Sample};
+    use crate::iop::challenger::{Challenger, RecursiveChallenger, TranscriptEntry};
     use crate::iop::generator::generate_partial_witness;
     use crate::iop::target::Target;
     use crate::iop::witness::{PartialWitness, Witness};
@@ -327,6 +372,58 @@ mod tests {
         assert_eq!(dedup_challenges, challenges);
     }
 
+    #[test]
+    fn test_with_domain() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        type H = <C as GenericConfig<D>>::InnerHasher;
+
+        let inputs = F::rand_vec(5);
+        let challenges = |mut challenger: Challenger<F, H>| {
+            challenger.observe_elements(&inputs);
+            challenger.get_n_challenges(3)
+        };
+
+        let domain_1 = challenges(Challenger::with_domain(1));
+        let domain_2 = challenges(Challenger::with_domain(2));
+        assert_ne!(domain_1, domain_2);
+        assert_eq!(domain_1, challenges(Challenger::with_domain(1)));
+        assert_eq!(
+            challenges(Challenger::with_domain(0)),
+            challenges(Challenger::new())
+        );
+
+        // Observing the tag as the first element doesn't reproduce the domain-separated transcript.
+        let mut tagged = Challenger::<F, H>::new();
+        tagged.observe_element(F::ONE);
+        assert_ne!(domain_1, challenges(tagged));
+    }
+
+    #[test]
+    fn test_transcript_log() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let mut challenger = Challenger::<F, <C as GenericConfig<D>>::InnerHasher>::new();
+        challenger.observe_element(F::ONE);
+        assert_eq!(challenger.transcript_log(), None);
+
+        challenger.enable_transcript_log();
+        let inputs = F::rand_vec(2);
+        challenger.observe_elements(&inputs);
+        let challenge = challenger.get_challenge();
+        assert_eq!(
+            challenger.transcript_log().unwrap(),
+            [
+                TranscriptEntry::Observed(inputs[0]),
+                TranscriptEntry::Observed(inputs[1]),
+                TranscriptEntry::Challenge(challenge),
+            ]
+        );
+    }
+
     /// Tests for consistency between `Challenger` and `RecursiveChallenger`.
     #[test]
     fn test_consistency() {
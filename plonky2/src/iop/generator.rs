@@ -1,23 +1,29 @@
 #[cfg(not(feature = "std"))]
 use alloc::{
     boxed::Box,
+    collections::BTreeMap,
     string::{String, ToString},
+    sync::Arc,
     vec,
     vec::Vec,
 };
+use core::any::Any;
 use core::fmt::Debug;
 use core::marker::PhantomData;
+#[cfg(feature = "std")]
+use std::{collections::BTreeMap, sync::Arc};
 
 use crate::field::extension::Extendable;
 use crate::field::types::Field;
-use crate::hash::hash_types::RichField;
+use crate::hash::hash_types::{HashOutTarget, RichField};
+use crate::hash::poseidon::PoseidonHash;
 use crate::iop::ext_target::ExtensionTarget;
 use crate::iop::target::Target;
 use crate::iop::wire::Wire;
 use crate::iop::witness::{PartialWitness, PartitionWitness, Witness, WitnessWrite};
 use crate::plonk::circuit_data::{CommonCircuitData, ProverOnlyCircuitData};
-use crate::plonk::config::GenericConfig;
-use crate::util::serialization::{Buffer, IoResult, Read, Write};
+use crate::plonk::config::{GenericConfig, Hasher};
+use crate::util::serialization::{Buffer, IoError, IoResult, Read, Write};
 
 /// Given a `PartitionWitness` that has only inputs set, populates the rest of the witness using the
 /// given set of generators.
@@ -31,12 +37,75 @@ pub fn generate_partial_witness<
     prover_data: &'a ProverOnlyCircuitData<F, C, D>,
     common_data: &'a CommonCircuitData<F, D>,
 ) -> PartitionWitness<'a, F> {
-    let config = &common_data.config;
-    let generators = &prover_data.generators;
-    let generator_indices_by_watches = &prover_data.generator_indices_by_watches;
+    let mut witness = PartitionWitness::new(
+        common_data.config.num_wires,
+        common_data.degree(),
+        &prover_data.representative_map,
+    );
+
+    for (t, v) in inputs.target_values.into_iter() {
+        witness.set_target(t, v);
+    }
+
+    // Initially, all generators are queued.
+    let num_generators = prover_data.generators.len();
+    let mut generator_is_expired = vec![false; num_generators];
+    let num_finished = run_generators(
+        &mut witness,
+        prover_data,
+        (0..num_generators).collect(),
+        &mut generator_is_expired,
+        |_| true,
+    );
+
+    let remaining_generators = num_generators - num_finished;
+    assert_eq!(
+        remaining_generators, 0,
+        "{} generators weren't run",
+        remaining_generators,
+    );
+
+    witness
+}
+
+/// Like `generate_partial_witness`, but runs the generators in phases: the generators whose
+/// indices are listed in `phases[i]` are all run before any generator of `phases[i + 1]`, and
+/// `on_phase` is called with `i` and the witness so far once phase `i` is done. This lets a
+/// pipelined prover start committing to some columns while the rest are being generated.
+///
+/// The phases must cover every generator exactly once, and their order must respect dependencies:
+/// a generator can only depend on values set by the inputs, by its own phase or by earlier
+/// phases, otherwise this panics as it won't have been run.
+pub fn generate_partial_witness_phased<
+    'a,
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    inputs: PartialWitness<F>,
+    prover_data: &'a ProverOnlyCircuitData<F, C, D>,
+    common_data: &'a CommonCircuitData<F, D>,
+    phases: &[Vec<usize>],
+    mut on_phase: impl FnMut(usize, &PartitionWitness<'a, F>),
+) -> PartitionWitness<'a, F> {
+    let num_generators = prover_data.generators.len();
+    let mut phase_of_generator = vec![None; num_generators];
+    for (phase, generator_indices) in phases.iter().enumerate() {
+        for &i in generator_indices {
+            assert!(i < num_generators, "Generator index {i} is out of range");
+            assert!(
+                phase_of_generator[i].replace(phase).is_none(),
+                "Generator {i} is listed in several phases"
+            );
+        }
+    }
+    assert!(
+        phase_of_generator.iter().all(Option::is_some),
+        "Every generator must be listed in a phase"
+    );
 
     let mut witness = PartitionWitness::new(
-        config.num_wires,
+        common_data.config.num_wires,
         common_data.degree(),
         &prover_data.representative_map,
     );
@@ -45,14 +114,43 @@ pub fn generate_partial_witness<
         witness.set_target(t, v);
     }
 
-    // Build a list of "pending" generators which are queued to be run. Initially, all generators
-    // are queued.
-    let mut pending_generator_indices: Vec<_> = (0..generators.len()).collect();
+    let mut generator_is_expired = vec![false; num_generators];
+    for (phase, generator_indices) in phases.iter().enumerate() {
+        let num_finished = run_generators(
+            &mut witness,
+            prover_data,
+            generator_indices.clone(),
+            &mut generator_is_expired,
+            |i| phase_of_generator[i] == Some(phase),
+        );
+
+        let remaining_generators = generator_indices.len() - num_finished;
+        assert_eq!(
+            remaining_generators, 0,
+            "{} generators of phase {} weren't run",
+            remaining_generators, phase,
+        );
+
+        on_phase(phase, &witness);
+    }
+
+    witness
+}
 
-    // We also track a list of "expired" generators which have already returned false.
-    let mut generator_is_expired = vec![false; generators.len()];
-    let mut remaining_generators = generators.len();
+/// Runs the pending generators, then any unfinished generators watching targets they populate,
+/// until no more progress is made. Only generators for which `in_scope` holds are re-queued.
+/// Returns the number of generators which finished.
+fn run_generators<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
+    witness: &mut PartitionWitness<F>,
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    mut pending_generator_indices: Vec<usize>,
+    generator_is_expired: &mut [bool],
+    in_scope: impl Fn(usize) -> bool,
+) -> usize {
+    let generators = &prover_data.generators;
+    let generator_indices_by_watches = &prover_data.generator_indices_by_watches;
 
+    let mut num_finished = 0;
     let mut buffer = GeneratedValues::empty();
 
     // Keep running generators until we fail to make progress.
@@ -64,10 +162,10 @@ pub fn generate_partial_witness<
                 continue;
             }
 
-            let finished = generators[generator_idx].0.run(&witness, &mut buffer);
+            let finished = generators[generator_idx].0.run(witness, &mut buffer);
             if finished {
                 generator_is_expired[generator_idx] = true;
-                remaining_generators -= 1;
+                num_finished += 1;
             }
 
             // Merge any generated values into our witness, and get a list of newly-populated
@@ -82,7 +180,9 @@ pub fn generate_partial_witness<
                 let opt_watchers = generator_indices_by_watches.get(&watch);
                 if let Some(watchers) = opt_watchers {
                     for &watching_generator_idx in watchers {
-                        if !generator_is_expired[watching_generator_idx] {
+                        if !generator_is_expired[watching_generator_idx]
+                            && in_scope(watching_generator_idx)
+                        {
                             next_pending_generator_indices.push(watching_generator_idx);
                         }
                     }
@@ -93,18 +193,35 @@ pub fn generate_partial_witness<
         pending_generator_indices = next_pending_generator_indices;
     }
 
-    assert_eq!(
-        remaining_generators, 0,
-        "{} generators weren't run",
-        remaining_generators,
-    );
+    num_finished
+}
 
-    witness
+/// Indexes generators by the representatives of the targets they watch, as given by
+/// `representative_index`. Indices are pushed in increasing order, so each list is sorted, which
+/// keeps generator scheduling deterministic.
+pub(crate) fn index_generators_by_watches<F: RichField + Extendable<D>, const D: usize>(
+    generators: &[WitnessGeneratorRef<F, D>],
+    representative_index: impl Fn(Target) -> usize,
+) -> BTreeMap<usize, Vec<usize>> {
+    let mut generator_indices_by_watches = BTreeMap::new();
+    for (i, generator) in generators.iter().enumerate() {
+        for watch in generator.0.watch_list() {
+            generator_indices_by_watches
+                .entry(representative_index(watch))
+                .or_insert_with(Vec::new)
+                .push(i);
+        }
+    }
+    for indices in generator_indices_by_watches.values_mut() {
+        indices.dedup();
+        indices.shrink_to_fit();
+    }
+    generator_indices_by_watches
 }
 
 /// A generator participates in the generation of the witness.
 pub trait WitnessGenerator<F: RichField + Extendable<D>, const D: usize>:
-    'static + Send + Sync + Debug
+    'static + Any + Send + Sync + Debug
 {
     fn id(&self) -> String;
 
@@ -134,6 +251,66 @@ impl<F: RichField + Extendable<D>, const D: usize> WitnessGeneratorRef<F, D> {
     pub fn new<G: WitnessGenerator<F, D>>(generator: G) -> WitnessGeneratorRef<F, D> {
         WitnessGeneratorRef(Box::new(generator))
     }
+
+    /// Returns the generator as a `T`, if that is its concrete type. Note that simple generators
+    /// are wrapped in a `SimpleGeneratorAdapter`. Generators shared between builders by
+    /// `CircuitBuilder::clone_partial` are seen as the generator they wrap.
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        let generator: &dyn WitnessGenerator<F, D> = match self.shared() {
+            Some(shared) => &**shared,
+            None => &*self.0,
+        };
+        (generator as &dyn Any).downcast_ref()
+    }
+
+    fn shared(&self) -> Option<&Arc<dyn WitnessGenerator<F, D>>> {
+        (&*self.0 as &dyn Any)
+            .downcast_ref::<SharedGenerator<F, D>>()
+            .map(|shared| &shared.0)
+    }
+
+    /// Returns two references to this generator, which share it. Generators can't be cloned, so
+    /// this is how `CircuitBuilder::clone_partial` gives each builder its own list of them.
+    pub(crate) fn share(self) -> (Self, Self) {
+        let already_shared = self.shared().cloned();
+        let shared = already_shared.unwrap_or_else(|| Arc::from(self.0));
+        (
+            Self::new(SharedGenerator(shared.clone())),
+            Self::new(SharedGenerator(shared)),
+        )
+    }
+}
+
+/// A generator behind a shared pointer, see `WitnessGeneratorRef::share`. It behaves like the
+/// generator it wraps, and is serialized as that generator.
+#[derive(Debug)]
+struct SharedGenerator<F: RichField + Extendable<D>, const D: usize>(
+    Arc<dyn WitnessGenerator<F, D>>,
+);
+
+impl<F: RichField + Extendable<D>, const D: usize> WitnessGenerator<F, D>
+    for SharedGenerator<F, D>
+{
+    fn id(&self) -> String {
+        self.0.id()
+    }
+
+    fn watch_list(&self) -> Vec<Target> {
+        self.0.watch_list()
+    }
+
+    fn run(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) -> bool {
+        self.0.run(witness, out_buffer)
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        self.0.serialize(dst, common_data)
+    }
+
+    fn deserialize(_src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        // The serialized bytes are those of the wrapped generator, which is deserialized instead.
+        Err(IoError)
+    }
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> PartialEq for WitnessGeneratorRef<F, D> {
@@ -235,6 +412,15 @@ pub struct SimpleGeneratorAdapter<
     inner: SG,
 }
 
+impl<F: RichField + Extendable<D>, SG: SimpleGenerator<F, D>, const D: usize>
+    SimpleGeneratorAdapter<F, SG, D>
+{
+    /// Returns the wrapped generator.
+    pub fn inner(&self) -> &SG {
+        &self.inner
+    }
+}
+
 impl<F: RichField + Extendable<D>, SG: SimpleGenerator<F, D>, const D: usize> WitnessGenerator<F, D>
     for SimpleGeneratorAdapter<F, SG, D>
 {
@@ -300,7 +486,8 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for Cop
     }
 }
 
-/// A generator for including a random value
+/// A generator for including a random value. If the target has already been set, e.g. by
+/// `prove_with_blinding_rng`, that value is kept.
 #[derive(Debug, Default)]
 pub struct RandomValueGenerator {
     pub(crate) target: Target,
@@ -315,7 +502,10 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for Ran
         Vec::new()
     }
 
-    fn run_once(&self, _witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        if witness.try_get_target(self.target).is_some() {
+            return;
+        }
         let random_value = F::rand();
         out_buffer.set_target(self.target, random_value);
     }
@@ -370,6 +560,43 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for Non
     }
 }
 
+/// A generator computing the inverse of the sum of `inputs`. If the sum is zero, it outputs zero.
+#[derive(Debug, Default)]
+pub struct InverseOfSumGenerator {
+    pub inputs: Vec<Target>,
+    pub output: Target,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for InverseOfSumGenerator {
+    fn id(&self) -> String {
+        "InverseOfSumGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.inputs.clone()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let sum = self
+            .inputs
+            .iter()
+            .map(|&t| witness.get_target(t))
+            .sum::<F>();
+        out_buffer.set_target(self.output, sum.try_inverse().unwrap_or(F::ZERO));
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target_vec(&self.inputs)?;
+        dst.write_target(self.output)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let inputs = src.read_target_vec()?;
+        let output = src.read_target()?;
+        Ok(Self { inputs, output })
+    }
+}
+
 /// Generator used to fill an extra constant.
 #[derive(Debug, Clone, Default)]
 pub struct ConstantGenerator<F: Field> {
@@ -418,3 +645,234 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D> for Con
         })
     }
 }
+
+/// Generator which sets `digest` to the Poseidon hash of `inputs`, typically the public inputs.
+#[derive(Debug, Default)]
+pub struct PublicInputsHashGenerator {
+    pub inputs: Vec<Target>,
+    pub digest: HashOutTarget,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for PublicInputsHashGenerator
+{
+    fn id(&self) -> String {
+        "PublicInputsHashGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.inputs.clone()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let inputs = witness.get_targets(&self.inputs);
+        out_buffer.set_hash_target(self.digest, PoseidonHash::hash_no_pad(&inputs));
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target_vec(&self.inputs)?;
+        dst.write_target_hash(&self.digest)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let inputs = src.read_target_vec()?;
+        let digest = src.read_target_hash()?;
+        Ok(Self { inputs, digest })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::{CircuitConfig, CircuitData};
+    use crate::plonk::config::PoseidonGoldilocksConfig;
+    use crate::plonk::verifier::verify;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    fn inverse_of_sum(values: &[u64]) -> Result<F> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let inputs = builder.add_virtual_targets(values.len());
+        for (&t, &v) in inputs.iter().zip(values) {
+            pw.set_target(t, F::from_canonical_u64(v));
+        }
+        let output = builder.add_virtual_public_input();
+        builder.add_simple_generator(InverseOfSumGenerator { inputs, output });
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        let result = proof.public_inputs[0];
+        verify(proof, &data.verifier_only, &data.common)?;
+
+        Ok(result)
+    }
+
+    #[test]
+    fn test_inverse_of_sum_generator() -> Result<()> {
+        assert_eq!(
+            inverse_of_sum(&[2, 3, 5])?,
+            F::from_canonical_u64(10).inverse()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_inverse_of_sum_generator_zero_sum() -> Result<()> {
+        assert_eq!(inverse_of_sum(&[])?, F::ZERO);
+        assert_eq!(inverse_of_sum(&[0, 0])?, F::ZERO);
+        Ok(())
+    }
+
+    #[test]
+    fn test_public_inputs_hash_generator() -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let values = (0..10).map(F::from_canonical_u64).collect::<Vec<_>>();
+        let inputs = builder.add_virtual_targets(values.len());
+        pw.set_target_arr(&inputs, &values);
+        builder.register_public_inputs(&inputs);
+        let digest = builder.public_inputs_hash_unconstrained();
+        builder.register_public_inputs(&digest.elements);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        assert_eq!(
+            proof.public_inputs[values.len()..],
+            PoseidonHash::hash_no_pad(&values).elements
+        );
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    /// Builds a small circuit, and splits its generators into rounds, each holding the generators
+    /// which can run once the previous rounds are done.
+    fn phased_circuit() -> (CircuitData<F, C, D>, PartialWitness<F>, Vec<Vec<usize>>) {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        let xy = builder.mul(x, y);
+        let sum = builder.add(xy, x);
+        let inv = builder.inverse(sum);
+        builder.register_public_input(inv);
+        let data = builder.build::<C>();
+
+        let mut inputs = PartialWitness::new();
+        inputs.set_target(x, F::from_canonical_u64(3));
+        inputs.set_target(y, F::from_canonical_u64(5));
+        // Fix the values which would otherwise be random, so that witnesses can be compared.
+        for &t in &data.prover_only.random_value_targets {
+            inputs.set_target(t, F::ZERO);
+        }
+
+        let generators = &data.prover_only.generators;
+        let mut witness = PartitionWitness::new(
+            data.common.config.num_wires,
+            data.common.degree(),
+            &data.prover_only.representative_map,
+        );
+        for (&t, &v) in &inputs.target_values {
+            witness.set_target(t, v);
+        }
+        let mut phases = Vec::new();
+        let mut remaining = (0..generators.len()).collect::<Vec<_>>();
+        while !remaining.is_empty() {
+            let mut buffer = GeneratedValues::empty();
+            let (ready, not_ready): (Vec<_>, Vec<_>) = remaining
+                .into_iter()
+                .partition(|&i| generators[i].0.run(&witness, &mut buffer));
+            assert!(!ready.is_empty());
+            for (t, v) in buffer.target_values {
+                witness.set_target(t, v);
+            }
+            phases.push(ready);
+            remaining = not_ready;
+        }
+        assert!(phases.len() > 1);
+
+        (data, inputs, phases)
+    }
+
+    #[test]
+    fn test_generate_partial_witness_phased() {
+        let (data, inputs, phases) = phased_circuit();
+
+        let mut completed_phases = Vec::new();
+        let phased = generate_partial_witness_phased(
+            inputs.clone(),
+            &data.prover_only,
+            &data.common,
+            &phases,
+            |phase, _| completed_phases.push(phase),
+        );
+        assert_eq!(completed_phases, (0..phases.len()).collect::<Vec<_>>());
+
+        let monolithic = generate_partial_witness(inputs, &data.prover_only, &data.common);
+        assert_eq!(
+            phased.full_witness().wire_values,
+            monolithic.full_witness().wire_values
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_generate_partial_witness_phased_wrong_order() {
+        let (data, inputs, mut phases) = phased_circuit();
+        phases.reverse();
+        generate_partial_witness_phased(
+            inputs,
+            &data.prover_only,
+            &data.common,
+            &phases,
+            |_, _| (),
+        );
+    }
+
+    #[test]
+    fn test_downcast_generator() {
+        let src = Target::VirtualTarget { index: 0 };
+        let dst = Target::VirtualTarget { index: 1 };
+        let generator = WitnessGeneratorRef::<F, D>::new(CopyGenerator { src, dst }.adapter());
+
+        let copy = generator
+            .downcast_ref::<SimpleGeneratorAdapter<F, CopyGenerator, D>>()
+            .expect("Expected a CopyGenerator");
+        assert_eq!((copy.inner.src, copy.inner.dst), (src, dst));
+
+        assert!(generator
+            .downcast_ref::<SimpleGeneratorAdapter<F, RandomValueGenerator, D>>()
+            .is_none());
+    }
+
+    #[test]
+    fn test_share_generator() {
+        let src = Target::VirtualTarget { index: 0 };
+        let dst = Target::VirtualTarget { index: 1 };
+        let generator = WitnessGeneratorRef::<F, D>::new(CopyGenerator { src, dst }.adapter());
+
+        // Sharing an already shared generator doesn't wrap it again.
+        let (first, second) = generator.share();
+        let (second, third) = second.share();
+        for shared in [first, second, third] {
+            assert_eq!(shared.0.id(), "CopyGenerator");
+            assert_eq!(shared.0.watch_list(), [src]);
+            let copy = shared
+                .downcast_ref::<SimpleGeneratorAdapter<F, CopyGenerator, D>>()
+                .expect("Expected a CopyGenerator");
+            assert_eq!((copy.inner.src, copy.inner.dst), (src, dst));
+
+            let wrapped = shared.shared().expect("Expected a SharedGenerator");
+            assert!((&**wrapped as &dyn Any).is::<SimpleGeneratorAdapter<F, CopyGenerator, D>>());
+        }
+    }
+}
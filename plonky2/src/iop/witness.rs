@@ -5,9 +5,10 @@ use hashbrown::HashMap;
 use itertools::{zip_eq, Itertools};
 
 use crate::field::extension::{Extendable, FieldExtension};
-use crate::field::types::Field;
+use crate::field::types::{Field, PrimeField};
 use crate::fri::structure::{FriOpenings, FriOpeningsTarget};
 use crate::fri::witness_util::set_fri_proof_target;
+use crate::gadgets::nonnative::{biguint_to_limbs, NonNativeTarget};
 use crate::hash::hash_types::{HashOut, HashOutTarget, MerkleCapTarget, RichField};
 use crate::hash::merkle_tree::MerkleCap;
 use crate::iop::ext_target::ExtensionTarget;
@@ -67,6 +68,13 @@ pub trait WitnessWrite<F: Field> {
         self.set_target(target.target, F::from_bool(value))
     }
 
+    fn set_nonnative_target<FF: PrimeField>(&mut self, target: &NonNativeTarget<FF>, value: FF) {
+        let limbs = biguint_to_limbs(&value.to_canonical_biguint(), target.limbs.len());
+        for (&t, limb) in target.limbs.iter().zip(limbs) {
+            self.set_target(t, F::from_canonical_usize(limb));
+        }
+    }
+
     /// Set the targets in a `ProofWithPublicInputsTarget` to their corresponding values in a
     /// `ProofWithPublicInputs`.
     fn set_proof_with_pis_target<C: GenericConfig<D, F = F>, const D: usize>(
@@ -274,6 +282,46 @@ impl<F: Field> PartialWitness<F> {
             target_values: HashMap::new(),
         }
     }
+
+    /// Checks that all of the given public input targets, typically
+    /// `ProverOnlyCircuitData::public_inputs`, have been set, and returns the unset ones otherwise.
+    /// This catches forgotten inputs before witness generation, but is only meaningful if all
+    /// public inputs are provided by the caller rather than computed by generators.
+    pub fn validate_public_inputs(&self, public_inputs: &[Target]) -> Result<(), Vec<Target>> {
+        let unset = public_inputs
+            .iter()
+            .filter(|&&t| !self.target_values.contains_key(&t))
+            .copied()
+            .collect::<Vec<_>>();
+        if unset.is_empty() {
+            Ok(())
+        } else {
+            Err(unset)
+        }
+    }
+
+    /// Adds all the values set in `other`. If a target is set to different values in both
+    /// witnesses, one such target is returned and `self` is left unchanged.
+    pub fn merge(&mut self, other: PartialWitness<F>) -> Result<(), Target> {
+        let conflict = other.target_values.iter().find(|&(target, value)| {
+            matches!(self.target_values.get(target), Some(old_value) if old_value != value)
+        });
+        if let Some((&target, _)) = conflict {
+            return Err(target);
+        }
+
+        self.target_values.extend(other.target_values);
+        Ok(())
+    }
+
+    /// Unsets the given public input targets, typically `ProverOnlyCircuitData::public_inputs`,
+    /// while keeping the rest of the witness. This allows proving again with new public inputs,
+    /// since setting a target which already has a different value panics.
+    pub fn reset_public_inputs(&mut self, public_inputs: &[Target]) {
+        for t in public_inputs {
+            self.target_values.remove(t);
+        }
+    }
 }
 
 impl<F: Field> WitnessWrite<F> for PartialWitness<F> {
@@ -364,3 +412,108 @@ impl<'a, F: Field> Witness<F> for PartitionWitness<'a, F> {
         self.values[rep_index]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::goldilocks_field::GoldilocksField;
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::PoseidonGoldilocksConfig;
+
+    #[test]
+    fn test_validate_public_inputs() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_public_input();
+        let y = builder.add_virtual_public_input();
+        let z = builder.add_virtual_public_input();
+        builder.add(x, y);
+        let data = builder.build::<C>();
+        let public_inputs = &data.prover_only.public_inputs;
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(y, F::ONE);
+        assert_eq!(pw.validate_public_inputs(public_inputs), Err(vec![x, z]));
+
+        pw.set_target(x, F::TWO);
+        pw.set_target(z, F::ZERO);
+        assert_eq!(pw.validate_public_inputs(public_inputs), Ok(()));
+    }
+
+    #[test]
+    fn test_reset_public_inputs() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_public_input();
+        let secret = builder.add_virtual_target();
+        let product = builder.mul(x, secret);
+        builder.register_public_input(product);
+        let data = builder.build::<C>();
+        let public_inputs = &data.prover_only.public_inputs;
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(secret, F::from_canonical_u64(7));
+        for x_value in [2, 3] {
+            pw.reset_public_inputs(public_inputs);
+            assert_eq!(pw.try_get_target(x), None);
+            assert_eq!(pw.get_target(secret), F::from_canonical_u64(7));
+
+            pw.set_target(x, F::from_canonical_u64(x_value));
+            let proof = data.prove(pw.clone())?;
+            assert_eq!(
+                proof.public_inputs,
+                [x_value, 7 * x_value].map(F::from_canonical_u64)
+            );
+            data.verify(proof)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge() {
+        type F = GoldilocksField;
+        let [a, b, c] = [0, 1, 2].map(|index| Target::VirtualTarget { index });
+
+        let mut pw = PartialWitness::<F>::new();
+        pw.set_target(a, F::ONE);
+        pw.set_target(b, F::TWO);
+        let mut other = PartialWitness::new();
+        // Agreeing on a value is not a conflict.
+        other.set_target(b, F::TWO);
+        other.set_target(c, F::ZERO);
+
+        assert_eq!(pw.merge(other), Ok(()));
+        assert_eq!(pw.try_get_target(a), Some(F::ONE));
+        assert_eq!(pw.try_get_target(b), Some(F::TWO));
+        assert_eq!(pw.try_get_target(c), Some(F::ZERO));
+    }
+
+    #[test]
+    fn test_merge_conflict() {
+        type F = GoldilocksField;
+        let [a, b, c] = [0, 1, 2].map(|index| Target::VirtualTarget { index });
+
+        let mut pw = PartialWitness::<F>::new();
+        pw.set_target(a, F::ONE);
+        pw.set_target(b, F::TWO);
+        let mut other = PartialWitness::new();
+        other.set_target(b, F::ONE);
+        other.set_target(c, F::ZERO);
+
+        assert_eq!(pw.merge(other), Err(b));
+        assert_eq!(pw.try_get_target(b), Some(F::TWO));
+        assert_eq!(pw.try_get_target(c), None);
+    }
+}
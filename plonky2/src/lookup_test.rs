@@ -202,6 +202,106 @@ fn test_two_luts() -> anyhow::Result<()> {
     Ok(())
 }
 
+// Looks up values in two tables with disjoint inputs, `table_index` selecting which one is used
+// for `input`.
+fn test_disjoint_luts(input: u16, table_index: usize) -> anyhow::Result<()> {
+    init_logger();
+
+    let config = CircuitConfig::standard_recursion_config();
+    let mut builder = CircuitBuilder::<F, D>::new(config);
+
+    let low_inputs = (0..4).collect::<Vec<u16>>();
+    let high_inputs = (4..12).collect::<Vec<u16>>();
+    let outputs = |inputs: &[u16]| inputs.iter().map(|&x| 3 * x).collect::<Vec<_>>();
+    let low_table = builder.add_lookup_table_from_table(&low_inputs, &outputs(&low_inputs));
+    let high_table = builder.add_lookup_table_from_table(&high_inputs, &outputs(&high_inputs));
+    assert_ne!(low_table, high_table);
+
+    // Use both tables, so that neither is left unused.
+    let low = builder.constant(F::ONE);
+    let low_output = builder.add_lookup_from_index(low, low_table);
+    let high = builder.constant(F::from_canonical_u16(5));
+    let high_output = builder.add_lookup_from_index(high, high_table);
+    builder.register_public_input(low_output);
+    builder.register_public_input(high_output);
+
+    let looking_in = builder.add_virtual_target();
+    let looking_out = builder.add_lookup_from_index(looking_in, table_index);
+    builder.register_public_input(looking_out);
+
+    let mut pw = PartialWitness::new();
+    pw.set_target(looking_in, F::from_canonical_u16(input));
+    let data = builder.build::<C>();
+    let mut timing = TimingTree::new("prove disjoint_luts", Level::Debug);
+    let proof = prove(&data.prover_only, &data.common, pw, &mut timing)?;
+    timing.print();
+
+    assert_eq!(
+        proof.public_inputs,
+        [3, 15, 3 * input].map(F::from_canonical_u16)
+    );
+    data.verify(proof)
+}
+
+#[test]
+fn test_disjoint_luts_valid() -> anyhow::Result<()> {
+    test_disjoint_luts(2, 0)?;
+    test_disjoint_luts(9, 1)
+}
+
+// An input of the first table isn't accepted by the second one.
+#[should_panic]
+#[test]
+fn test_disjoint_luts_wrong_table() {
+    test_disjoint_luts(2, 1).unwrap();
+}
+
+/// The S-box of the PRESENT block cipher.
+const PRESENT_SBOX: [u16; 16] = [
+    0xc, 0x5, 0x6, 0xb, 0x9, 0x0, 0xa, 0xd, 0x3, 0xe, 0xf, 0x8, 0x4, 0x7, 0x1, 0x2,
+];
+
+fn test_sbox4(inputs: &[u16]) -> anyhow::Result<()> {
+    init_logger();
+
+    let config = CircuitConfig::standard_recursion_config();
+    let mut builder = CircuitBuilder::<F, D>::new(config);
+    let table = PRESENT_SBOX.map(F::from_canonical_u16);
+
+    let mut pw = PartialWitness::new();
+    for &input in inputs {
+        let input_target = builder.add_virtual_target();
+        pw.set_target(input_target, F::from_canonical_u16(input));
+        let output = builder.sbox4(input_target, table);
+        builder.register_public_input(output);
+    }
+    // All the S-box lookups share a single table.
+    assert_eq!(builder.get_luts_length(), 1);
+
+    let data = builder.build::<C>();
+    let mut timing = TimingTree::new("prove sbox4", Level::Debug);
+    let proof = prove(&data.prover_only, &data.common, pw, &mut timing)?;
+    timing.print();
+
+    let expected = inputs
+        .iter()
+        .map(|&x| F::from_canonical_u16(PRESENT_SBOX[x as usize]))
+        .collect::<Vec<_>>();
+    assert_eq!(proof.public_inputs, expected);
+    data.verify(proof)
+}
+
+#[test]
+fn test_sbox4_all_inputs() -> anyhow::Result<()> {
+    test_sbox4(&(0..16).collect::<Vec<_>>())
+}
+
+#[should_panic]
+#[test]
+fn test_sbox4_input_too_large() {
+    test_sbox4(&[16]).unwrap();
+}
+
 #[test]
 fn test_different_inputs() -> anyhow::Result<()> {
     init_logger();
@@ -1,10 +1,15 @@
 //! Logic for building plonky2 circuits.
 
 #[cfg(not(feature = "std"))]
-use alloc::{collections::BTreeMap, sync::Arc, vec, vec::Vec};
+use alloc::{
+    string::{String, ToString},
+    sync::Arc,
+    vec,
+    vec::Vec,
+};
 use core::cmp::max;
 #[cfg(feature = "std")]
-use std::{collections::BTreeMap, sync::Arc, time::Instant};
+use std::{sync::Arc, time::Instant};
 
 use hashbrown::{HashMap, HashSet};
 use itertools::Itertools;
@@ -34,10 +39,12 @@ use crate::hash::merkle_proofs::MerkleProofTarget;
 use crate::hash::merkle_tree::MerkleCap;
 use crate::iop::ext_target::ExtensionTarget;
 use crate::iop::generator::{
-    ConstantGenerator, CopyGenerator, RandomValueGenerator, SimpleGenerator, WitnessGeneratorRef,
+    index_generators_by_watches, ConstantGenerator, CopyGenerator, PublicInputsHashGenerator,
+    RandomValueGenerator, SimpleGenerator, WitnessGeneratorRef,
 };
 use crate::iop::target::{BoolTarget, Target};
 use crate::iop::wire::Wire;
+use crate::iop::witness::WitnessWrite;
 use crate::plonk::circuit_data::{
     CircuitConfig, CircuitData, CommonCircuitData, MockCircuitData, ProverCircuitData,
     ProverOnlyCircuitData, VerifierCircuitData, VerifierCircuitTarget, VerifierOnlyCircuitData,
@@ -84,6 +91,36 @@ pub struct LookupWire {
     pub first_lut_gate: usize,
 }
 
+/// A public input registered with [`CircuitBuilder::register_public_input_optional`], which each
+/// proof may choose to reveal or keep private.
+///
+/// The circuit always has the same public inputs: a visibility flag, and a slot holding the value
+/// when the flag is set, or zero otherwise.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PublicInputHandle {
+    /// Whether the value is revealed in the proof.
+    pub is_public: BoolTarget,
+    /// Index of the visibility flag in the public inputs.
+    pub flag_index: usize,
+    /// Index of the (possibly masked) value in the public inputs.
+    pub value_index: usize,
+}
+
+impl PublicInputHandle {
+    /// Sets whether the value should be revealed in the proof generated from `witness`.
+    pub fn set_public<F: Field>(&self, witness: &mut impl WitnessWrite<F>, public: bool) {
+        witness.set_bool_target(self.is_public, public);
+    }
+
+    /// Returns the value of this input in the given public inputs, or `None` if it was kept
+    /// private.
+    pub fn value<F: Field>(&self, public_inputs: &[F]) -> Option<F> {
+        public_inputs[self.flag_index]
+            .is_one()
+            .then(|| public_inputs[self.value_index])
+    }
+}
+
 /// Structure used to construct a plonky2 circuit. It provides all the necessary toolkit that,
 /// from an initial circuit configuration, will enable one to design a circuit and its associated
 /// prover/verifier data.
@@ -145,6 +182,10 @@ pub struct CircuitBuilder<F: RichField + Extendable<D>, const D: usize> {
     /// Defaults to the empty vector.
     domain_separator: Option<Vec<F>>,
 
+    /// The size of the LDE domain, if it was set with `set_lde_size` rather than derived from the
+    /// circuit degree and the rate.
+    lde_size: Option<usize>,
+
     /// The types of gates used in this circuit.
     gates: HashSet<GateRef<F, D>>,
 
@@ -180,6 +221,12 @@ pub struct CircuitBuilder<F: RichField + Extendable<D>, const D: usize> {
     /// List of constant generators used to fill the constant wires.
     constant_generators: Vec<ConstantGenerator<F>>,
 
+    /// Targets filled by `RandomValueGenerator`s.
+    random_value_targets: Vec<Target>,
+
+    /// Targets registered with `add_named_target`, by name.
+    named_targets: HashMap<String, Target>,
+
     /// Rows for each LUT: [`LookupWire`] contains: first [`LookupGate`], first and last
     /// [LookupTableGate](crate::gates::lookup_table::LookupTableGate).
     lookup_rows: Vec<LookupWire>,
@@ -209,6 +256,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         let builder = CircuitBuilder {
             config,
             domain_separator: None,
+            lde_size: None,
             gates: HashSet::new(),
             gate_instances: Vec::new(),
             public_inputs: Vec::new(),
@@ -222,6 +270,8 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             arithmetic_results: HashMap::new(),
             current_slots: HashMap::new(),
             constant_generators: Vec::new(),
+            random_value_targets: Vec::new(),
+            named_targets: HashMap::new(),
             lookup_rows: Vec::new(),
             lut_to_lookups: Vec::new(),
             luts: Vec::new(),
@@ -232,6 +282,44 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         builder
     }
 
+    /// Returns a copy of this builder, so that a partially built circuit can be finished in several
+    /// ways. The gates, copy constraints and memoized operations are copied, which costs about as
+    /// much time and memory as the builder itself holds. Generators can't be copied, so the ones
+    /// added so far are moved behind shared pointers and shared by both builders, hence `&mut self`.
+    pub fn clone_partial(&mut self) -> Self {
+        let (generators, cloned_generators) = core::mem::take(&mut self.generators)
+            .into_iter()
+            .map(WitnessGeneratorRef::share)
+            .unzip();
+        self.generators = generators;
+
+        Self {
+            config: self.config.clone(),
+            domain_separator: self.domain_separator.clone(),
+            lde_size: self.lde_size,
+            gates: self.gates.clone(),
+            gate_instances: self.gate_instances.clone(),
+            public_inputs: self.public_inputs.clone(),
+            virtual_target_index: self.virtual_target_index,
+            copy_constraints: self.copy_constraints.clone(),
+            context_log: self.context_log.clone(),
+            generators: cloned_generators,
+            constants_to_targets: self.constants_to_targets.clone(),
+            targets_to_constants: self.targets_to_constants.clone(),
+            base_arithmetic_results: self.base_arithmetic_results.clone(),
+            arithmetic_results: self.arithmetic_results.clone(),
+            current_slots: self.current_slots.clone(),
+            constant_generators: self.constant_generators.clone(),
+            random_value_targets: self.random_value_targets.clone(),
+            named_targets: self.named_targets.clone(),
+            lookup_rows: self.lookup_rows.clone(),
+            lut_to_lookups: self.lut_to_lookups.clone(),
+            luts: self.luts.clone(),
+            goal_common_data: self.goal_common_data.clone(),
+            verifier_data_public_input: self.verifier_data_public_input.clone(),
+        }
+    }
+
     /// Assert that the configuration used to create this `CircuitBuilder` is consistent,
     /// i.e. that the different parameters meet the targeted security level.
     fn check_config(&self) {
@@ -262,6 +350,19 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         self.domain_separator = Some(separator);
     }
 
+    /// Sets the size of the low-degree extension domain used by the prover, e.g. to match an
+    /// external verifier, instead of `degree * 2^rate_bits`. The rate is raised accordingly when
+    /// the circuit is built, so `lde_size` must be a power of two which is at least the default
+    /// size; it then also exceeds the degree of the quotient polynomial, which is required for
+    /// soundness.
+    pub fn set_lde_size(&mut self, lde_size: usize) {
+        assert!(
+            lde_size.is_power_of_two(),
+            "LDE size {lde_size} is not a power of two"
+        );
+        self.lde_size = Some(lde_size);
+    }
+
     /// Outputs the number of gates in this circuit.
     pub fn num_gates(&self) -> usize {
         self.gate_instances.len()
@@ -277,6 +378,35 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         targets.iter().for_each(|&t| self.register_public_input(t));
     }
 
+    /// Registers the given target as a public input which each proof may choose to keep private,
+    /// using [`PublicInputHandle::set_public`]. Both choices use the same circuit.
+    pub fn register_public_input_optional(&mut self, target: Target) -> PublicInputHandle {
+        let is_public = self.add_virtual_bool_target_safe();
+        let masked = self.mul(is_public.target, target);
+
+        let flag_index = self.num_public_inputs();
+        self.register_public_input(is_public.target);
+        self.register_public_input(masked);
+
+        PublicInputHandle {
+            is_public,
+            flag_index,
+            value_index: flag_index + 1,
+        }
+    }
+
+    /// Returns a hash which the prover sets to the Poseidon hash of the public inputs registered
+    /// so far. The hash is computed by a generator only and is not constrained, so it is up to the
+    /// caller to constrain it, or to check it against the hash computed by the verifier.
+    pub fn public_inputs_hash_unconstrained(&mut self) -> HashOutTarget {
+        let digest = self.add_virtual_hash();
+        self.add_simple_generator(PublicInputsHashGenerator {
+            inputs: self.public_inputs.clone(),
+            digest,
+        });
+        digest
+    }
+
     /// Outputs the number of public inputs in this circuit.
     pub fn num_public_inputs(&self) -> usize {
         self.public_inputs.len()
@@ -328,6 +458,23 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         Target::VirtualTarget { index }
     }
 
+    /// Adds a new "virtual" target which can later be retrieved by `name` with
+    /// `get_named_target`, instead of being passed around. Panics if `name` is already taken.
+    pub fn add_named_target(&mut self, name: &str) -> Target {
+        assert!(
+            !self.named_targets.contains_key(name),
+            "A target named {name} already exists"
+        );
+        let target = self.add_virtual_target();
+        self.named_targets.insert(name.to_string(), target);
+        target
+    }
+
+    /// Returns the target registered under `name` with `add_named_target`, if any.
+    pub fn get_named_target(&self, name: &str) -> Option<Target> {
+        self.named_targets.get(name).copied()
+    }
+
     /// Adds `n` new "virtual" targets.
     pub fn add_virtual_targets(&mut self, n: usize) -> Vec<Target> {
         (0..n).map(|_i| self.add_virtual_target()).collect()
@@ -350,6 +497,13 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     }
 
     /// Adds a new `MerkleCapTarget`, consisting in `1 << cap_height` `HashOutTarget`.
+    ///
+    /// The cap height is chosen per commitment and need not match
+    /// `self.config.fri_config.cap_height`, which only applies to the oracles committed to by the
+    /// prover. A taller cap shortens each Merkle proof by one sibling per level, at the cost of
+    /// twice as many cap hashes. For FRI oracles, every one of the `num_query_rounds` queries
+    /// opens a path of `degree_bits + rate_bits - cap_height` siblings, so a taller cap saves
+    /// that many hashes per query while the cap itself is sent only once.
     pub fn add_virtual_cap(&mut self, cap_height: usize) -> MerkleCapTarget {
         MerkleCapTarget(self.add_virtual_hashes(1 << cap_height))
     }
@@ -372,6 +526,20 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         }
     }
 
+    /// Adds a new `MerkleProofTarget` for a tree with `1 << tree_height` leaves, opened against a
+    /// cap of height `cap_height` (see `add_virtual_cap`).
+    pub fn add_virtual_merkle_proof_to_cap(
+        &mut self,
+        tree_height: usize,
+        cap_height: usize,
+    ) -> MerkleProofTarget {
+        assert!(
+            cap_height <= tree_height,
+            "cap_height={cap_height} should be at most tree_height={tree_height}"
+        );
+        self.add_virtual_merkle_proof(tree_height - cap_height)
+    }
+
     pub fn add_virtual_extension_target(&mut self) -> ExtensionTarget<D> {
         ExtensionTarget(self.add_virtual_targets(D).try_into().unwrap())
     }
@@ -545,6 +713,12 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         self.connect(x, zero);
     }
 
+    /// If `selector` is true, enforces that `value` is 0, i.e. enforces `selector * value = 0`.
+    pub fn conditional_assert_zero(&mut self, selector: BoolTarget, value: Target) {
+        let constr = self.mul(selector.target, value);
+        self.assert_zero(constr);
+    }
+
     /// Enforces that a routable `Target` value is 1, using Plonk's permutation argument.
     ///
     /// # Example
@@ -912,9 +1086,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         for _ in 0..regular_poly_openings {
             let row = self.add_gate(NoopGate, vec![]);
             for w in 0..num_wires {
-                self.add_simple_generator(RandomValueGenerator {
-                    target: Target::Wire(Wire { row, column: w }),
-                });
+                self.add_random_value_generator(Target::Wire(Wire { row, column: w }));
             }
         }
 
@@ -926,12 +1098,10 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             let gate_2 = self.add_gate(NoopGate, vec![]);
 
             for w in 0..num_routed_wires {
-                self.add_simple_generator(RandomValueGenerator {
-                    target: Target::Wire(Wire {
-                        row: gate_1,
-                        column: w,
-                    }),
-                });
+                self.add_random_value_generator(Target::Wire(Wire {
+                    row: gate_1,
+                    column: w,
+                }));
                 self.generate_copy(
                     Target::Wire(Wire {
                         row: gate_1,
@@ -1030,12 +1200,17 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
     /// See <https://github.com/0xPolygonZero/plonky2/issues/456>.
     fn randomize_unused_pi_wires(&mut self, pi_gate: usize) {
         for wire in PublicInputGate::wires_public_inputs_hash().end..self.config.num_wires {
-            self.add_simple_generator(RandomValueGenerator {
-                target: Target::wire(pi_gate, wire),
-            });
+            self.add_random_value_generator(Target::wire(pi_gate, wire));
         }
     }
 
+    /// Adds a `RandomValueGenerator` for `target`, recording it so that the prover can supply
+    /// the random value itself.
+    fn add_random_value_generator(&mut self, target: Target) {
+        self.add_simple_generator(RandomValueGenerator { target });
+        self.random_value_targets.push(target);
+    }
+
     /// Builds a "full circuit", with both prover and verifier data.
     pub fn build_with_options<C: GenericConfig<D, F = F>>(
         self,
@@ -1116,6 +1291,20 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         let degree = self.gate_instances.len();
         debug!("Degree after blinding & padding: {}", degree);
         let degree_bits = log2_strict(degree);
+        let rate_bits = match self.lde_size {
+            Some(lde_size) => {
+                let lde_bits = log2_strict(lde_size);
+                assert!(
+                    lde_bits >= degree_bits + rate_bits,
+                    "LDE size {} is smaller than the default of {}",
+                    lde_size,
+                    degree << rate_bits
+                );
+                self.config.fri_config.rate_bits = lde_bits - degree_bits;
+                lde_bits - degree_bits
+            }
+            None => rate_bits,
+        };
         let fri_params = self.fri_params(degree_bits);
         assert!(
             fri_params.total_arities() <= degree_bits + rate_bits - cap_height,
@@ -1195,22 +1384,9 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
                 .collect(),
         );
 
-        // Index generator indices by their watched targets.
-        let mut generator_indices_by_watches = BTreeMap::new();
-        for (i, generator) in self.generators.iter().enumerate() {
-            for watch in generator.0.watch_list() {
-                let watch_index = forest.target_index(watch);
-                let watch_rep_index = forest.parents[watch_index];
-                generator_indices_by_watches
-                    .entry(watch_rep_index)
-                    .or_insert_with(Vec::new)
-                    .push(i);
-            }
-        }
-        for indices in generator_indices_by_watches.values_mut() {
-            indices.dedup();
-            indices.shrink_to_fit();
-        }
+        let generator_indices_by_watches = index_generators_by_watches(&self.generators, |t| {
+            forest.parents[forest.target_index(t)]
+        });
 
         let num_gate_constraints = gates
             .iter()
@@ -1279,6 +1455,7 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
             circuit_digest,
             lookup_rows: self.lookup_rows.clone(),
             lut_to_lookups: self.lut_to_lookups.clone(),
+            random_value_targets: self.random_value_targets,
         };
 
         let verifier_only = VerifierOnlyCircuitData::<C, D> {
@@ -1325,3 +1502,254 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilder<F, D> {
         circuit_data.verifier_data()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::config::PoseidonGoldilocksConfig;
+    use crate::plonk::verifier::verify;
+
+    #[test]
+    fn test_named_targets() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_named_target("x");
+        let y = builder.add_named_target("y");
+        assert_ne!(x, y);
+        assert_eq!(builder.get_named_target("x"), Some(x));
+        assert_eq!(builder.get_named_target("y"), Some(y));
+        assert_eq!(builder.get_named_target("z"), None);
+
+        // A gadget can retrieve the targets by name rather than taking them as arguments.
+        let x = builder.get_named_target("x").unwrap();
+        let y = builder.get_named_target("y").unwrap();
+        let z = builder.mul(x, y);
+        builder.register_public_input(z);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(x, F::from_canonical_u64(3));
+        pw.set_target(y, F::from_canonical_u64(5));
+        let proof = data.prove(pw)?;
+        assert_eq!(proof.public_inputs, [F::from_canonical_u64(15)]);
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_duplicate_named_target() {
+        const D: usize = 2;
+        type F = <PoseidonGoldilocksConfig as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        builder.add_named_target("x");
+        builder.add_named_target("x");
+    }
+
+    #[test]
+    fn test_register_public_input_optional() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        let x_squared = builder.square(x);
+        builder.register_public_input(y);
+        let handle = builder.register_public_input_optional(x_squared);
+        let data = builder.build::<C>();
+
+        let prove = |public| {
+            let mut pw = PartialWitness::new();
+            pw.set_target(x, F::from_canonical_u64(5));
+            pw.set_target(y, F::from_canonical_u64(7));
+            handle.set_public(&mut pw, public);
+            data.prove(pw)
+        };
+
+        let public_proof = prove(true)?;
+        assert_eq!(
+            handle.value(&public_proof.public_inputs),
+            Some(F::from_canonical_u64(25))
+        );
+        data.verify(public_proof)?;
+
+        let private_proof = prove(false)?;
+        assert_eq!(handle.value(&private_proof.public_inputs), None);
+        assert!(!private_proof
+            .public_inputs
+            .contains(&F::from_canonical_u64(25)));
+        data.verify(private_proof)
+    }
+
+    #[test]
+    fn test_generator_indices_by_watches_deterministic() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let build = || {
+            let config = CircuitConfig::standard_recursion_config();
+            let mut builder = CircuitBuilder::<F, D>::new(config);
+            let x = builder.add_virtual_public_input();
+            let y = builder.add_virtual_target();
+            let bits = builder.split_le(x, 32);
+            let prod = builder.mul(x, y);
+            let eq = builder.is_equal(prod, bits[0].target);
+            builder.register_public_input(eq.target);
+            builder.build::<C>()
+        };
+
+        let watches = build().prover_only.generator_indices_by_watches;
+        assert_eq!(watches, build().prover_only.generator_indices_by_watches);
+        for indices in watches.values() {
+            assert!(indices.windows(2).all(|w| w[0] < w[1]));
+        }
+    }
+
+    #[test]
+    fn test_clone_partial() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut prefix = CircuitBuilder::<F, D>::new(config);
+        let x = prefix.add_virtual_target();
+        let y = prefix.add_virtual_target();
+        let xy = prefix.mul(x, y);
+        // The inverse is computed by a generator, which both branches share.
+        let inv = prefix.inverse(xy);
+        prefix.register_public_input(xy);
+        let num_gates = prefix.num_gates();
+
+        let mut sum_builder = prefix.clone_partial();
+        let sum = sum_builder.add(inv, x);
+        sum_builder.register_public_input(sum);
+
+        let mut product_builder = prefix.clone_partial();
+        let product = product_builder.mul(inv, y);
+        product_builder.register_public_input(product);
+
+        // Finishing the clones doesn't affect the original.
+        assert_eq!(prefix.num_gates(), num_gates);
+        assert_eq!(prefix.num_public_inputs(), 1);
+
+        let x_value = F::from_canonical_u64(3);
+        let y_value = F::from_canonical_u64(5);
+        let inv_value = (x_value * y_value).inverse();
+        for (builder, expected) in [
+            (sum_builder, inv_value + x_value),
+            (product_builder, inv_value * y_value),
+        ] {
+            let mut pw = PartialWitness::new();
+            pw.set_target(x, x_value);
+            pw.set_target(y, y_value);
+
+            let data = builder.build::<C>();
+            let proof = data.prove(pw)?;
+            assert_eq!(proof.public_inputs, [F::from_canonical_u64(15), expected]);
+            data.verify(proof)?;
+        }
+
+        Ok(())
+    }
+
+    fn test_conditional_assert_zero(selector: bool, value: u64) -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let s = builder.add_virtual_bool_target_safe();
+        let x = builder.add_virtual_target();
+        builder.conditional_assert_zero(s, x);
+        pw.set_bool_target(s, selector);
+        pw.set_target(x, F::from_canonical_u64(value));
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        verify(proof, &data.verifier_only, &data.common)
+    }
+
+    #[test]
+    fn test_conditional_assert_zero_inactive() -> Result<()> {
+        test_conditional_assert_zero(false, 0)?;
+        test_conditional_assert_zero(false, 7)
+    }
+
+    #[test]
+    fn test_conditional_assert_zero_active() -> Result<()> {
+        test_conditional_assert_zero(true, 0)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_conditional_assert_zero_active_nonzero() {
+        test_conditional_assert_zero(true, 7).unwrap();
+    }
+
+    #[test]
+    fn test_set_lde_size() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let build = |lde_size: Option<usize>| {
+            let config = CircuitConfig::standard_recursion_config();
+            let mut builder = CircuitBuilder::<F, D>::new(config);
+            let x = builder.add_virtual_public_input();
+            let y = builder.square(x);
+            builder.register_public_input(y);
+            if let Some(lde_size) = lde_size {
+                builder.set_lde_size(lde_size);
+            }
+            builder.build::<C>()
+        };
+
+        let default_lde_size = build(None).common.lde_size();
+        let data = build(Some(4 * default_lde_size));
+        assert_eq!(data.common.lde_size(), 4 * default_lde_size);
+        assert_eq!(
+            data.common.config.fri_config.rate_bits,
+            CircuitConfig::standard_recursion_config()
+                .fri_config
+                .rate_bits
+                + 2
+        );
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(data.prover_only.public_inputs[0], F::from_canonical_u64(3));
+        let proof = data.prove(pw)?;
+        data.verify(proof)
+    }
+
+    #[test]
+    #[should_panic(expected = "smaller than the default")]
+    fn test_set_lde_size_too_small() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config.clone());
+        builder.add_gate(NoopGate, vec![]);
+        // Even the smallest circuits have more than one row, so this is below the default.
+        builder.set_lde_size(1 << config.fri_config.rate_bits);
+        builder.build::<C>();
+    }
+}
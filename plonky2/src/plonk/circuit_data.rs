@@ -14,11 +14,16 @@
 
 #[cfg(not(feature = "std"))]
 use alloc::{collections::BTreeMap, vec, vec::Vec};
+use core::mem::size_of;
 use core::ops::{Range, RangeFrom};
 #[cfg(feature = "std")]
 use std::collections::BTreeMap;
 
 use anyhow::Result;
+use hashbrown::HashMap;
+#[cfg(feature = "parallel")]
+use plonky2_maybe_rayon::rayon;
+use rand::RngCore;
 use serde::Serialize;
 
 use super::circuit_builder::LookupWire;
@@ -35,19 +40,29 @@ use crate::fri::{FriConfig, FriParams};
 use crate::gates::gate::GateRef;
 use crate::gates::lookup::Lookup;
 use crate::gates::lookup_table::LookupTable;
-use crate::gates::selectors::SelectorsInfo;
-use crate::hash::hash_types::{HashOutTarget, MerkleCapTarget, RichField};
+use crate::gates::selectors::{SelectorsInfo, UNUSED_SELECTOR};
+use crate::hash::hash_types::{HashOut, HashOutTarget, MerkleCapTarget, RichField};
 use crate::hash::merkle_tree::MerkleCap;
+use crate::hash::poseidon::PoseidonHash;
+use crate::iop::challenger::TranscriptEntry;
 use crate::iop::ext_target::ExtensionTarget;
 use crate::iop::generator::{generate_partial_witness, WitnessGeneratorRef};
 use crate::iop::target::Target;
+use crate::iop::wire::Wire;
 use crate::iop::witness::{PartialWitness, PartitionWitness};
 use crate::plonk::circuit_builder::CircuitBuilder;
 use crate::plonk::config::{GenericConfig, Hasher};
 use crate::plonk::plonk_common::PlonkOracle;
 use crate::plonk::proof::{CompressedProofWithPublicInputs, ProofWithPublicInputs};
-use crate::plonk::prover::prove;
-use crate::plonk::verifier::verify;
+#[cfg(feature = "parallel")]
+use crate::plonk::prover::prove_in_pool;
+use crate::plonk::prover::{
+    debug_prove, gate_constraint_failures, prove, prove_and_return_witness,
+    prove_with_blinding_rng, prove_with_observer, prove_with_transcript_log,
+    prove_with_witness_hook, ConstraintFailure, ProverObserver,
+};
+use crate::plonk::symbolic::{gate_constraints, SymbolicConstraint};
+use crate::plonk::verifier::{verify, verify_with_public_inputs_hash};
 use crate::util::serialization::{
     Buffer, GateSerializer, IoResult, Read, WitnessGeneratorSerializer, Write,
 };
@@ -98,6 +113,11 @@ impl CircuitConfig {
     }
 
     /// A typical recursion config, without zero-knowledge, targeting ~100 bit security.
+    ///
+    /// It is wide enough for the gates of the recursive verifier (in particular `PoseidonGate`,
+    /// which needs 135 wires), so a circuit verifying a proof made with this config can itself use
+    /// it. A rate of `1/8` keeps the number of FRI queries, and thus the cost of verifying in a
+    /// circuit, low, at the expense of slower proving than a higher rate.
     pub const fn standard_recursion_config() -> Self {
         Self {
             num_wires: 135,
@@ -132,6 +152,10 @@ impl CircuitConfig {
         }
     }
 
+    /// Like `standard_recursion_config`, but with zero-knowledge. Blinding adds a few rows and
+    /// salts the Merkle leaves, so circuits are slightly larger and proving is slightly slower.
+    /// Only the outermost proof needs to be zero-knowledge, so inner recursion layers can use
+    /// `standard_recursion_config`.
     pub fn standard_recursion_zk_config() -> Self {
         CircuitConfig {
             zero_knowledge: true,
@@ -195,10 +219,152 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         )
     }
 
+    /// Like `prove`, but draws the blinding randomness from `rng`.
+    pub fn prove_with_blinding_rng<R: RngCore + ?Sized>(
+        &self,
+        inputs: PartialWitness<F>,
+        rng: &mut R,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        prove_with_blinding_rng::<F, C, R, D>(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            rng,
+            &mut TimingTree::default(),
+        )
+    }
+
+    /// Like `prove`, but also returns the log of the Fiat-Shamir transcript.
+    #[allow(clippy::type_complexity)]
+    pub fn prove_with_transcript_log(
+        &self,
+        inputs: PartialWitness<F>,
+    ) -> Result<(ProofWithPublicInputs<F, C, D>, Vec<TranscriptEntry<F>>)> {
+        prove_with_transcript_log::<F, C, D>(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            &mut TimingTree::default(),
+        )
+    }
+
+    /// Like `prove`, but runs in `pool` rather than in the global thread pool.
+    #[cfg(feature = "parallel")]
+    pub fn prove_in_pool(
+        &self,
+        inputs: PartialWitness<F>,
+        pool: &rayon::ThreadPool,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        prove_in_pool::<F, C, D>(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            pool,
+            &mut TimingTree::default(),
+        )
+    }
+
+    /// Like `prove`, but reports its progress to `observer`.
+    pub fn prove_with_observer(
+        &self,
+        inputs: PartialWitness<F>,
+        observer: &mut dyn ProverObserver,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        prove_with_observer::<F, C, D>(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            observer,
+            &mut TimingTree::default(),
+        )
+    }
+
+    /// Like `prove`, but lets `hook` inspect or adjust the generated witness before it is committed
+    /// to. The hook must keep every constraint satisfied.
+    pub fn prove_with_witness_hook<'a>(
+        &'a self,
+        inputs: PartialWitness<F>,
+        hook: impl FnOnce(&mut PartitionWitness<'a, F>),
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        prove_with_witness_hook::<F, C, D>(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            hook,
+            &mut TimingTree::default(),
+        )
+    }
+
+    /// Checks that `witness`, as returned by witness generation, satisfies every gate constraint,
+    /// and returns all the constraints which aren't satisfied otherwise. This is much faster than
+    /// proving. Copy constraints hold by construction of the witness, but lookups aren't checked.
+    pub fn check_witness(
+        &self,
+        witness: &PartitionWitness<F>,
+    ) -> Result<(), Vec<ConstraintFailure>> {
+        let failures = gate_constraint_failures(&self.prover_only, &self.common, witness);
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+
+    /// Like `prove`, but reports the first unsatisfied gate constraint, if any. Slow; for debugging.
+    pub fn debug_prove(&self, inputs: PartialWitness<F>) -> Result<ProofWithPublicInputs<F, C, D>> {
+        debug_prove::<F, C, D>(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            &mut TimingTree::default(),
+        )
+    }
+
+    /// Like `prove`, but also returns the generated witness for inspecting target values.
+    pub fn prove_and_return_witness(
+        &self,
+        inputs: PartialWitness<F>,
+    ) -> Result<(ProofWithPublicInputs<F, C, D>, PartitionWitness<F>)> {
+        prove_and_return_witness::<F, C, D>(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            &mut TimingTree::default(),
+        )
+    }
+
     pub fn verify(&self, proof_with_pis: ProofWithPublicInputs<F, C, D>) -> Result<()> {
         verify::<F, C, D>(proof_with_pis, &self.verifier_only, &self.common)
     }
 
+    /// Returns the index of each public input along with a wire holding its value.
+    pub fn public_input_wires(&self) -> Vec<(usize, Wire)> {
+        self.prover_only.public_input_wires(&self.common)
+    }
+
+    /// Returns the virtual targets which no gate constrains, which a prover can choose freely.
+    pub fn find_unconstrained_targets(&self) -> Vec<Target> {
+        self.prover_only.find_unconstrained_targets(&self.common)
+    }
+
+    /// Returns the gate of each row along with the constants configuring it.
+    pub fn gates_with_constants(&self) -> Vec<(&GateRef<F, D>, Vec<F>)> {
+        self.prover_only.gates_with_constants(&self.common)
+    }
+
+    pub fn verify_with_public_inputs_hash(
+        &self,
+        proof_with_pis: ProofWithPublicInputs<F, C, D>,
+        expected_public_inputs_hash: <<C as GenericConfig<D>>::InnerHasher as Hasher<F>>::Hash,
+    ) -> Result<()> {
+        verify_with_public_inputs_hash::<F, C, D>(
+            proof_with_pis,
+            expected_public_inputs_hash,
+            &self.verifier_only,
+            &self.common,
+        )
+    }
+
     pub fn verify_compressed(
         &self,
         compressed_proof_with_pis: CompressedProofWithPublicInputs<F, C, D>,
@@ -292,6 +458,105 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
             &mut TimingTree::default(),
         )
     }
+
+    /// Like `prove`, but draws the blinding randomness from `rng`.
+    pub fn prove_with_blinding_rng<R: RngCore + ?Sized>(
+        &self,
+        inputs: PartialWitness<F>,
+        rng: &mut R,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        prove_with_blinding_rng::<F, C, R, D>(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            rng,
+            &mut TimingTree::default(),
+        )
+    }
+
+    /// Like `prove`, but also returns the log of the Fiat-Shamir transcript.
+    #[allow(clippy::type_complexity)]
+    pub fn prove_with_transcript_log(
+        &self,
+        inputs: PartialWitness<F>,
+    ) -> Result<(ProofWithPublicInputs<F, C, D>, Vec<TranscriptEntry<F>>)> {
+        prove_with_transcript_log::<F, C, D>(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            &mut TimingTree::default(),
+        )
+    }
+
+    /// Like `prove`, but runs in `pool` rather than in the global thread pool.
+    #[cfg(feature = "parallel")]
+    pub fn prove_in_pool(
+        &self,
+        inputs: PartialWitness<F>,
+        pool: &rayon::ThreadPool,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        prove_in_pool::<F, C, D>(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            pool,
+            &mut TimingTree::default(),
+        )
+    }
+
+    /// Like `prove`, but reports its progress to `observer`.
+    pub fn prove_with_observer(
+        &self,
+        inputs: PartialWitness<F>,
+        observer: &mut dyn ProverObserver,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        prove_with_observer::<F, C, D>(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            observer,
+            &mut TimingTree::default(),
+        )
+    }
+
+    /// Like `prove`, but lets `hook` inspect or adjust the generated witness before it is committed
+    /// to. The hook must keep every constraint satisfied.
+    pub fn prove_with_witness_hook<'a>(
+        &'a self,
+        inputs: PartialWitness<F>,
+        hook: impl FnOnce(&mut PartitionWitness<'a, F>),
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        prove_with_witness_hook::<F, C, D>(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            hook,
+            &mut TimingTree::default(),
+        )
+    }
+
+    /// Like `prove`, but reports the first unsatisfied gate constraint, if any. Slow; for debugging.
+    pub fn debug_prove(&self, inputs: PartialWitness<F>) -> Result<ProofWithPublicInputs<F, C, D>> {
+        debug_prove::<F, C, D>(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            &mut TimingTree::default(),
+        )
+    }
+
+    /// Like `prove`, but also returns the generated witness for inspecting target values.
+    pub fn prove_and_return_witness(
+        &self,
+        inputs: PartialWitness<F>,
+    ) -> Result<(ProofWithPublicInputs<F, C, D>, PartitionWitness<F>)> {
+        prove_and_return_witness::<F, C, D>(
+            &self.prover_only,
+            &self.common,
+            inputs,
+            &mut TimingTree::default(),
+        )
+    }
 }
 
 /// Circuit data required by the prover.
@@ -326,6 +591,19 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         verify::<F, C, D>(proof_with_pis, &self.verifier_only, &self.common)
     }
 
+    pub fn verify_with_public_inputs_hash(
+        &self,
+        proof_with_pis: ProofWithPublicInputs<F, C, D>,
+        expected_public_inputs_hash: <<C as GenericConfig<D>>::InnerHasher as Hasher<F>>::Hash,
+    ) -> Result<()> {
+        verify_with_public_inputs_hash::<F, C, D>(
+            proof_with_pis,
+            expected_public_inputs_hash,
+            &self.verifier_only,
+            &self.common,
+        )
+    }
+
     pub fn verify_compressed(
         &self,
         compressed_proof_with_pis: CompressedProofWithPublicInputs<F, C, D>,
@@ -365,6 +643,8 @@ pub struct ProverOnlyCircuitData<
     pub lookup_rows: Vec<LookupWire>,
     /// A vector of (looking_in, looking_out) pairs for each lookup table index.
     pub lut_to_lookups: Vec<Lookup>,
+    /// Targets which are filled with random values during witness generation, e.g. for blinding.
+    pub random_value_targets: Vec<Target>,
 }
 
 impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
@@ -388,6 +668,152 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         let mut buffer = Buffer::new(bytes);
         buffer.read_prover_only_circuit_data(generator_serializer, common_data)
     }
+
+    /// Returns the index of each public input along with a wire holding its value. Public inputs
+    /// are often virtual targets, in which case the first routed wire (in row-major order) copy
+    /// constrained to the target is returned.
+    pub fn public_input_wires(&self, common_data: &CommonCircuitData<F, D>) -> Vec<(usize, Wire)> {
+        let num_wires = common_data.config.num_wires;
+        let degree = common_data.degree();
+
+        let mut wires_by_representative = HashMap::new();
+        for row in 0..degree {
+            for column in 0..common_data.config.num_routed_wires {
+                let wire = Wire { row, column };
+                let index = Target::Wire(wire).index(num_wires, degree);
+                wires_by_representative
+                    .entry(self.representative_map[index])
+                    .or_insert(wire);
+            }
+        }
+
+        self.public_inputs
+            .iter()
+            .enumerate()
+            .map(|(i, target)| {
+                let representative = self.representative_map[target.index(num_wires, degree)];
+                // Every public input is hashed, so it is always copied to some routed wire.
+                let wire = wires_by_representative[&representative];
+                (i, wire)
+            })
+            .collect()
+    }
+
+    /// Returns the classes of targets which are constrained to be equal, i.e. the partition induced
+    /// by all the copy constraints of the circuit, including those added by the builder itself.
+    /// The individual `connect` calls can't be recovered, but two targets were (transitively)
+    /// connected iff they are in the same class. Targets which aren't connected to any other
+    /// target are omitted. Classes are sorted by their first target, in the order of
+    /// `Target::index`.
+    pub fn copy_constraints(&self, common_data: &CommonCircuitData<F, D>) -> Vec<Vec<Target>> {
+        let num_wires = common_data.config.num_wires;
+        let num_wire_targets = num_wires * common_data.degree();
+        let target = |index: usize| {
+            if index < num_wire_targets {
+                Target::wire(index / num_wires, index % num_wires)
+            } else {
+                Target::VirtualTarget {
+                    index: index - num_wire_targets,
+                }
+            }
+        };
+
+        let mut class_sizes = HashMap::new();
+        for &representative in &self.representative_map {
+            *class_sizes.entry(representative).or_insert(0) += 1;
+        }
+
+        let mut classes: Vec<Vec<Target>> = Vec::new();
+        let mut class_indices = HashMap::new();
+        for (index, &representative) in self.representative_map.iter().enumerate() {
+            if class_sizes[&representative] < 2 {
+                continue;
+            }
+            let class_index = *class_indices.entry(representative).or_insert_with(|| {
+                classes.push(Vec::new());
+                classes.len() - 1
+            });
+            classes[class_index].push(target(index));
+        }
+        classes
+    }
+
+    /// Returns the virtual targets which no gate constrains, i.e. those which aren't copy
+    /// constrained to any wire used by the gate of its row. Their values don't affect the proof,
+    /// so if a generator sets one of them, e.g. as advice for a gadget, a prover can choose it
+    /// freely; this usually means that a `connect` was forgotten.
+    ///
+    /// Unused wires are not reported, as most of them are padding.
+    pub fn find_unconstrained_targets(&self, common_data: &CommonCircuitData<F, D>) -> Vec<Target> {
+        let num_wires = common_data.config.num_wires;
+        let degree = common_data.degree();
+
+        let mut constrained = vec![false; self.representative_map.len()];
+        for (row, gate_index) in self.gate_indices(common_data).into_iter().enumerate() {
+            for column in 0..common_data.gates[gate_index].0.num_wires() {
+                let index = Target::wire(row, column).index(num_wires, degree);
+                constrained[self.representative_map[index]] = true;
+            }
+        }
+
+        (num_wires * degree..self.representative_map.len())
+            .filter(|&index| !constrained[self.representative_map[index]])
+            .map(|index| Target::VirtualTarget {
+                index: index - num_wires * degree,
+            })
+            .collect()
+    }
+
+    /// Returns the gate of each row, in row order, along with the constants configuring it, e.g.
+    /// for analysis passes over the built circuit. They are read back from the committed constant
+    /// polynomials, so they include the values of `ConstantGate`s and the padding `NoopGate`s.
+    pub fn gates_with_constants<'a>(
+        &self,
+        common_data: &'a CommonCircuitData<F, D>,
+    ) -> Vec<(&'a GateRef<F, D>, Vec<F>)> {
+        // The gates' own constants follow the selectors and the lookup selectors.
+        let first_gate_constant =
+            common_data.selectors_info.num_selectors() + common_data.num_lookup_selectors;
+        let constants = self.constants_sigmas_commitment.polynomials
+            [first_gate_constant..common_data.num_constants]
+            .iter()
+            .map(|poly| poly.clone().fft().values)
+            .collect::<Vec<_>>();
+
+        self.gate_indices(common_data)
+            .into_iter()
+            .enumerate()
+            .map(|(row, gate_index)| {
+                let gate = &common_data.gates[gate_index];
+                let gate_constants = constants[..gate.0.num_constants()]
+                    .iter()
+                    .map(|values| values[row])
+                    .collect();
+                (gate, gate_constants)
+            })
+            .collect()
+    }
+
+    /// Returns the index in `common_data.gates` of the gate of each row.
+    fn gate_indices(&self, common_data: &CommonCircuitData<F, D>) -> Vec<usize> {
+        // The selector polynomials come first among the constants, and hold the index of the
+        // gate of each row in the group of that gate, and `UNUSED_SELECTOR` in other groups.
+        let selectors = self.constants_sigmas_commitment.polynomials
+            [..common_data.selectors_info.num_selectors()]
+            .iter()
+            .map(|poly| poly.clone().fft().values)
+            .collect::<Vec<_>>();
+
+        (0..common_data.degree())
+            .map(|row| {
+                selectors
+                    .iter()
+                    .map(|values| values[row].to_canonical_u64() as usize)
+                    .find(|&index| index != UNUSED_SELECTOR)
+                    .expect("Every row has a gate")
+            })
+            .collect()
+    }
 }
 
 /// Circuit data required by the verifier, but not the prover.
@@ -468,10 +894,13 @@ impl<F: RichField + Extendable<D>, const D: usize> CommonCircuitData<F, D> {
         buffer.read_common_circuit_data(gate_serializer)
     }
 
+    /// The base-2 logarithm of `degree`, i.e. of the number of rows after padding.
     pub const fn degree_bits(&self) -> usize {
         self.fri_params.degree_bits
     }
 
+    /// The number of rows of the circuit after padding, which is the size of the witness
+    /// polynomials used when proving.
     pub const fn degree(&self) -> usize {
         1 << self.degree_bits()
     }
@@ -492,10 +921,112 @@ impl<F: RichField + Extendable<D>, const D: usize> CommonCircuitData<F, D> {
             .expect("No gates?")
     }
 
+    /// The number of degree-`n` chunks the quotient polynomial is split into.
+    pub const fn quotient_degree_factor(&self) -> usize {
+        self.quotient_degree_factor
+    }
+
     pub const fn quotient_degree(&self) -> usize {
         self.quotient_degree_factor * self.degree()
     }
 
+    /// Estimates the number of bytes used by the witness while proving. This includes
+    /// - the `PartitionWitness` values of all wire targets, i.e. `num_wires * degree` entries of
+    ///   `Option<F>`, and
+    /// - the `MatrixWitness` built from them, i.e. `num_wires * degree` field elements.
+    ///
+    /// Virtual targets and the generators themselves are not included, as their number is only
+    /// known to the prover data, so the actual footprint is somewhat larger.
+    pub fn estimate_witness_bytes(&self) -> usize {
+        let num_wire_targets = self.config.num_wires * self.degree();
+        num_wire_targets * (size_of::<Option<F>>() + size_of::<F>())
+    }
+
+    /// Returns the constraints of each gate of the circuit in symbolic form, in terms of the wires
+    /// and constants of a row, e.g. to check them with an external tool. The selector filters are
+    /// not included. Panics if a gate evaluates its constraints with operations other than
+    /// extension arithmetic.
+    pub fn export_constraints(&self) -> Vec<SymbolicConstraint<F::Extension>> {
+        self.gates
+            .iter()
+            .flat_map(|gate| gate_constraints(gate, &self.config))
+            .collect()
+    }
+
+    /// A hash of the circuit's shape: its configuration, FRI parameters, gates, size and lookup
+    /// tables. It is the same for circuits built by the same code, no matter in which order the
+    /// generators were added, which makes it usable for caching. Unlike the circuit digest, it
+    /// doesn't commit to the wiring or the constants of the circuit.
+    pub fn content_digest(&self) -> HashOut<F> {
+        let config = &self.config;
+        let fri_params = &self.fri_params;
+        let mut values = vec![
+            config.num_wires,
+            config.num_routed_wires,
+            config.num_constants,
+            config.use_base_arithmetic_gate as usize,
+            config.security_bits,
+            config.num_challenges,
+            config.zero_knowledge as usize,
+            config.max_quotient_degree_factor,
+            fri_params.config.rate_bits,
+            fri_params.config.cap_height,
+            fri_params.config.proof_of_work_bits as usize,
+            fri_params.config.num_query_rounds,
+            fri_params.hiding as usize,
+            fri_params.degree_bits,
+            fri_params.reduction_arity_bits.len(),
+        ];
+        values.extend(&fri_params.reduction_arity_bits);
+
+        // Gates are sorted when the circuit is built, so their order is canonical. Their ids
+        // include their parameters.
+        values.push(self.gates.len());
+        for gate in &self.gates {
+            let id = gate.0.id();
+            values.push(id.len());
+            values.extend(id.bytes().map(usize::from));
+        }
+
+        values.extend([
+            self.quotient_degree_factor,
+            self.num_gate_constraints,
+            self.num_constants,
+            self.num_public_inputs,
+            self.num_partial_products,
+            self.num_lookup_polys,
+            self.num_lookup_selectors,
+            self.luts.len(),
+        ]);
+        for lut in &self.luts {
+            values.push(lut.len());
+            for &(input, output) in lut.iter() {
+                values.extend([input as usize, output as usize]);
+            }
+        }
+
+        let elements = values
+            .into_iter()
+            .map(F::from_canonical_usize)
+            .collect::<Vec<_>>();
+        PoseidonHash::hash_no_pad(&elements)
+    }
+
+    /// The largest degree of any gate constraint, once multiplied by the gate's selector filter.
+    /// This is at most `quotient_degree_factor + 1`.
+    pub fn max_constraint_degree(&self) -> usize {
+        let many_selectors = self.selectors_info.num_selectors() > 1;
+        self.gates
+            .iter()
+            .enumerate()
+            .map(|(i, g)| {
+                let group = &self.selectors_info.groups[self.selectors_info.selector_indices[i]];
+                g.0.degree() + group.len() - 1 + many_selectors as usize
+            })
+            .max()
+            .expect("No gates?")
+    }
+
     /// Range of the constants polynomials in the `constants_sigmas_commitment`.
     pub const fn constants_range(&self) -> Range<usize> {
         0..self.num_constants
@@ -676,3 +1207,698 @@ pub struct VerifierCircuitTarget {
     /// seed Fiat-Shamir.
     pub circuit_digest: HashOutTarget,
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use hashbrown::HashSet;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::gates::arithmetic_base::ArithmeticGate;
+    use crate::gates::constant::ConstantGate;
+    use crate::gates::gate::Gate;
+    use crate::gates::noop::NoopGate;
+    use crate::gates::public_input::PublicInputGate;
+    use crate::iop::generator::CopyGenerator;
+    use crate::iop::witness::{PartialWitness, Witness, WitnessWrite};
+    use crate::plonk::config::PoseidonGoldilocksConfig;
+    use crate::util::serialization::{
+        DefaultGateSerializer, DefaultGeneratorSerializer, CIRCUIT_FORMAT_MAGIC,
+        CIRCUIT_FORMAT_VERSION,
+    };
+
+    #[test]
+    fn test_max_constraint_degree() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config.clone());
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        builder.mul(x, y);
+        pw.set_target(x, F::from_canonical_u64(2));
+        pw.set_target(y, F::from_canonical_u64(3));
+        let data = builder.build::<C>();
+        let common = &data.common;
+
+        assert_eq!(
+            common.quotient_degree_factor(),
+            config.max_quotient_degree_factor
+        );
+        assert_eq!(
+            common.quotient_degree(),
+            common.quotient_degree_factor() * common.degree()
+        );
+
+        // Without any public inputs, no Poseidon gate is needed and `ArithmeticGate` has the
+        // highest degree. All gates then fit in a single selector group, whose filter has degree
+        // `num_gates - 1`.
+        let arithmetic_degree =
+            <ArithmeticGate as Gate<F, D>>::degree(&ArithmeticGate::new_from_config(&config));
+        assert_eq!(common.constraint_degree(), arithmetic_degree);
+        assert_eq!(common.selectors_info.num_selectors(), 1);
+        assert_eq!(
+            common.max_constraint_degree(),
+            arithmetic_degree + common.gates.len() - 1
+        );
+        assert!(common.max_constraint_degree() <= common.quotient_degree_factor() + 1);
+
+        data.verify(data.prove(pw)?)
+    }
+
+    #[test]
+    fn test_common_data_format_version() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        builder.square(x);
+        let data = builder.build::<C>();
+
+        let gate_serializer = DefaultGateSerializer;
+        let bytes = data.common.to_bytes(&gate_serializer).unwrap();
+        assert_eq!(bytes[..4], CIRCUIT_FORMAT_MAGIC);
+        let common =
+            CommonCircuitData::<F, D>::from_bytes(bytes.clone(), &gate_serializer).unwrap();
+        assert_eq!(common, data.common);
+
+        // Data written with another format version is rejected.
+        let mut other_version_bytes = bytes.clone();
+        other_version_bytes[4..8].copy_from_slice(&(CIRCUIT_FORMAT_VERSION + 1).to_le_bytes());
+        assert!(
+            CommonCircuitData::<F, D>::from_bytes(other_version_bytes, &gate_serializer).is_err()
+        );
+
+        // So is data without a format header.
+        let untagged_bytes = bytes[8..].to_vec();
+        assert!(CommonCircuitData::<F, D>::from_bytes(untagged_bytes, &gate_serializer).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prove_with_blinding_rng() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_zk_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        let z = builder.mul(x, y);
+        builder.register_public_input(z);
+        let data = builder.build::<C>();
+
+        let witness = || {
+            let mut pw = PartialWitness::new();
+            pw.set_target(x, F::from_canonical_u64(3));
+            pw.set_target(y, F::from_canonical_u64(5));
+            pw
+        };
+
+        let proof_0 = data.prove_with_blinding_rng(witness(), &mut ChaCha8Rng::seed_from_u64(0))?;
+        let proof_1 = data.prove_with_blinding_rng(witness(), &mut ChaCha8Rng::seed_from_u64(0))?;
+        let proof_2 = data.prove_with_blinding_rng(witness(), &mut ChaCha8Rng::seed_from_u64(1))?;
+        assert_eq!(proof_0.to_bytes(), proof_1.to_bytes());
+        assert_ne!(proof_0.to_bytes(), proof_2.to_bytes());
+
+        data.verify(proof_0)?;
+        data.verify(proof_2)
+    }
+
+    #[test]
+    fn test_public_input_wires() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_public_input();
+        let y = builder.add_virtual_target();
+        let z = builder.mul(x, y);
+        builder.register_public_input(y);
+        builder.register_public_input(z);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(x, F::from_canonical_u64(3));
+        pw.set_target(y, F::from_canonical_u64(5));
+        let witness = generate_partial_witness(pw.clone(), &data.prover_only, &data.common);
+        let public_inputs = witness.get_targets(&data.prover_only.public_inputs);
+
+        let public_input_wires = data.public_input_wires();
+        assert_eq!(public_input_wires.len(), 3);
+        for (i, wire) in public_input_wires {
+            assert_eq!(witness.get_wire(wire), public_inputs[i]);
+        }
+
+        data.verify(data.prove(pw)?)
+    }
+
+    #[test]
+    fn test_prove_and_return_witness() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        let xy = builder.mul(x, y);
+        let z = builder.add(xy, x);
+        builder.register_public_input(z);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(x, F::from_canonical_u64(3));
+        pw.set_target(y, F::from_canonical_u64(5));
+        let (proof, witness) = data.prove_and_return_witness(pw)?;
+        assert_eq!(
+            witness.get_targets(&[xy, z]),
+            [F::from_canonical_u64(15), F::from_canonical_u64(18)]
+        );
+
+        data.verify(proof)
+    }
+
+    #[test]
+    fn test_debug_prove() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        let z = builder.mul(x, y);
+        builder.register_public_input(z);
+        // `PublicInputGate` has no generators, so the wires of an extra instance can be set to
+        // anything. Its constraints require them to hold the public inputs hash.
+        let row = builder.add_gate(PublicInputGate, vec![]);
+        let data = builder.build::<C>();
+        let public_inputs_hash =
+            <C as GenericConfig<D>>::InnerHasher::hash_no_pad(&[F::from_canonical_u64(15)]);
+
+        let witness = |first_hash_wire| {
+            let mut pw = PartialWitness::new();
+            pw.set_target(x, F::from_canonical_u64(3));
+            pw.set_target(y, F::from_canonical_u64(5));
+            pw.set_target(Target::wire(row, 0), first_hash_wire);
+            for i in 1..4 {
+                pw.set_target(Target::wire(row, i), public_inputs_hash.elements[i]);
+            }
+            pw
+        };
+
+        data.verify(data.debug_prove(witness(public_inputs_hash.elements[0]))?)?;
+
+        let err = data
+            .debug_prove(witness(public_inputs_hash.elements[0] + F::ONE))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("PublicInputGate"), "{}", err);
+        assert!(err.contains(&format!("row {}", row)), "{}", err);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_estimate_witness_bytes() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        let z = builder.mul(x, y);
+        builder.register_public_input(z);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(x, F::from_canonical_u64(3));
+        pw.set_target(y, F::from_canonical_u64(5));
+        let partition_witness = generate_partial_witness(pw, &data.prover_only, &data.common);
+        let partition_bytes = partition_witness.values.len() * size_of::<Option<F>>();
+        let matrix_witness = partition_witness.full_witness();
+        let matrix_bytes = matrix_witness
+            .wire_values
+            .iter()
+            .map(|column| column.len() * size_of::<F>())
+            .sum::<usize>();
+
+        // The estimate only leaves out the values of virtual targets.
+        let num_wire_targets = data.common.config.num_wires * data.common.degree();
+        let num_virtual_targets = data.prover_only.representative_map.len() - num_wire_targets;
+        assert_eq!(
+            data.common.estimate_witness_bytes() + num_virtual_targets * size_of::<Option<F>>(),
+            partition_bytes + matrix_bytes
+        );
+    }
+
+    #[test]
+    fn test_prove_with_transcript_log() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        let z = builder.mul(x, y);
+        builder.register_public_input(z);
+        let data = builder.build::<C>();
+
+        let witness = |a: u64| {
+            let mut pw = PartialWitness::new();
+            pw.set_target(x, F::from_canonical_u64(a));
+            pw.set_target(y, F::from_canonical_u64(5));
+            // Fix the values which would otherwise be random, e.g. in unused public input wires.
+            for &t in &data.prover_only.random_value_targets {
+                pw.set_target(t, F::ZERO);
+            }
+            pw
+        };
+
+        // Without zero-knowledge or random values, proving is deterministic, and so is the
+        // transcript.
+        let (proof_0, log_0) = data.prove_with_transcript_log(witness(3))?;
+        let (proof_1, log_1) = data.prove_with_transcript_log(witness(3))?;
+        let (proof_2, log_2) = data.prove_with_transcript_log(witness(4))?;
+        assert!(!log_0.is_empty());
+        assert!(log_0
+            .iter()
+            .any(|entry| matches!(entry, TranscriptEntry::Challenge(_))));
+        assert_eq!(log_0, log_1);
+        assert_ne!(log_0, log_2);
+        assert_eq!(proof_0, proof_1);
+
+        data.verify(proof_0)?;
+        data.verify(proof_2)
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_prove_in_pool() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut mul_builder = CircuitBuilder::<F, D>::new(config.clone());
+        let x = mul_builder.add_virtual_target();
+        let y = mul_builder.add_virtual_target();
+        let xy = mul_builder.mul(x, y);
+        mul_builder.register_public_input(xy);
+        let mul_data = mul_builder.build::<C>();
+
+        let mut square_builder = CircuitBuilder::<F, D>::new(config);
+        let z = square_builder.add_virtual_target();
+        let z_squared = square_builder.square(z);
+        square_builder.register_public_input(z_squared);
+        let square_data = square_builder.build::<C>();
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+        let (mul_proof, square_proof) = std::thread::scope(|scope| {
+            let mul_job = scope.spawn(|| {
+                let mut pw = PartialWitness::new();
+                pw.set_target(x, F::from_canonical_u64(3));
+                pw.set_target(y, F::from_canonical_u64(5));
+                mul_data.prove_in_pool(pw, &pool)
+            });
+            let square_job = scope.spawn(|| {
+                let mut pw = PartialWitness::new();
+                pw.set_target(z, F::from_canonical_u64(7));
+                square_data.prove_in_pool(pw, &pool)
+            });
+            (mul_job.join().unwrap(), square_job.join().unwrap())
+        });
+        let (mul_proof, square_proof) = (mul_proof?, square_proof?);
+
+        assert_eq!(mul_proof.public_inputs, [F::from_canonical_u64(15)]);
+        assert_eq!(square_proof.public_inputs, [F::from_canonical_u64(49)]);
+        mul_data.verify(mul_proof)?;
+        square_data.verify(square_proof)
+    }
+
+    #[test]
+    fn test_prove_with_observer() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        #[derive(Default)]
+        struct RecordingObserver {
+            events: Vec<&'static str>,
+            folds: Vec<(usize, usize)>,
+        }
+
+        impl ProverObserver for RecordingObserver {
+            fn witness_generated(&mut self) {
+                self.events.push("witness generated");
+            }
+
+            fn committed_trace(&mut self) {
+                self.events.push("committed trace");
+            }
+
+            fn computed_quotient(&mut self) {
+                self.events.push("computed quotient");
+            }
+
+            fn fri_fold(&mut self, fold: usize, num_folds: usize) {
+                self.events.push("FRI fold");
+                self.folds.push((fold, num_folds));
+            }
+        }
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        let z = builder.mul(x, y);
+        builder.register_public_input(z);
+        // Make the circuit large enough for FRI to fold at least once.
+        for _ in 0..1000 {
+            builder.add_gate(NoopGate, vec![]);
+        }
+        let data = builder.build::<C>();
+        let num_folds = data.common.fri_params.reduction_arity_bits.len();
+        assert!(num_folds > 0);
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(x, F::from_canonical_u64(3));
+        pw.set_target(y, F::from_canonical_u64(5));
+        let mut observer = RecordingObserver::default();
+        let proof = data.prove_with_observer(pw, &mut observer)?;
+
+        let mut expected = vec!["witness generated", "committed trace", "computed quotient"];
+        expected.extend(vec!["FRI fold"; num_folds]);
+        assert_eq!(observer.events, expected);
+        assert_eq!(
+            observer.folds,
+            (1..=num_folds)
+                .map(|fold| (fold, num_folds))
+                .collect::<Vec<_>>()
+        );
+
+        data.verify(proof)
+    }
+
+    #[test]
+    fn test_prove_with_witness_hook() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        let z = builder.mul(x, y);
+        builder.register_public_input(z);
+        let data = builder.build::<C>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target(x, F::from_canonical_u64(3));
+        pw.set_target(y, F::from_canonical_u64(5));
+        // Fix the values which would otherwise be random, e.g. in unused public input wires.
+        for &t in &data.prover_only.random_value_targets {
+            pw.set_target(t, F::ZERO);
+        }
+
+        // Without zero-knowledge or random values, proving is deterministic, so a no-op hook
+        // doesn't change the proof.
+        let mut hook_called = false;
+        let proof = data.prove_with_witness_hook(pw.clone(), |witness| {
+            assert_eq!(witness.get_target(z), F::from_canonical_u64(15));
+            hook_called = true;
+        })?;
+        assert!(hook_called);
+        assert_eq!(proof, data.prove(pw)?);
+
+        data.verify(proof)
+    }
+
+    #[test]
+    fn test_find_unconstrained_targets() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        pw.set_target(x, F::from_canonical_u64(3));
+        pw.set_target(y, F::from_canonical_u64(5));
+        let xy = builder.mul(x, y);
+        builder.register_public_input(xy);
+
+        // Advice which is set by a generator, but which is never connected to a gate.
+        let advice = builder.add_virtual_target();
+        builder.add_simple_generator(CopyGenerator {
+            src: xy,
+            dst: advice,
+        });
+
+        let data = builder.build::<C>();
+        let unconstrained = data.find_unconstrained_targets();
+        assert!(unconstrained.contains(&advice));
+        assert!(!unconstrained.contains(&x));
+        assert!(!unconstrained.contains(&y));
+
+        // The circuit is still valid, whatever the advice is.
+        let proof = data.prove(pw)?;
+        data.verify(proof)
+    }
+
+    #[test]
+    fn test_gates_with_constants() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config.clone());
+        let arithmetic_constants = vec![F::TWO, F::from_canonical_u64(3)];
+        builder.add_gate(
+            ArithmeticGate::new_from_config(&config),
+            arithmetic_constants.clone(),
+        );
+        // Constants are only placed in a `ConstantGate` by `build`.
+        let c = F::from_canonical_u64(123_456);
+        let c_target = builder.constant(c);
+        builder.register_public_input(c_target);
+
+        let data = builder.build::<C>();
+        let gates = data.gates_with_constants();
+        assert_eq!(gates.len(), data.common.degree());
+        for (gate, constants) in &gates {
+            assert_eq!(constants.len(), gate.0.num_constants());
+        }
+
+        assert!(gates[0].0 .0.id().starts_with("ArithmeticGate"));
+        assert_eq!(gates[0].1, arithmetic_constants);
+        assert!(gates.iter().any(|(gate, constants)| {
+            gate.0.id().starts_with("ConstantGate") && constants.contains(&c)
+        }));
+    }
+
+    #[test]
+    fn test_degree_bits() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        for _ in 0..1000 {
+            builder.add_gate(NoopGate, vec![]);
+        }
+        let num_gates = builder.num_gates();
+        let data = builder.build::<C>();
+
+        let degree_bits = data.common.degree_bits();
+        assert_eq!(1 << degree_bits, data.common.degree());
+        // Building adds a few gates, e.g. for hashing the public inputs, before padding.
+        assert!(data.common.degree() >= num_gates);
+        assert_eq!(data.prover_only.subgroup.len(), data.common.degree());
+    }
+
+    #[test]
+    fn test_content_digest() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let build = |num_muls: usize| {
+            let config = CircuitConfig::standard_recursion_config();
+            let mut builder = CircuitBuilder::<F, D>::new(config);
+            let mut x = builder.add_virtual_target();
+            for _ in 0..num_muls {
+                x = builder.square(x);
+            }
+            builder.register_public_input(x);
+            builder.build::<C>().common
+        };
+
+        assert_eq!(build(10).content_digest(), build(10).content_digest());
+        // The gates are the same, but there is one more public input.
+        let mut other = build(10);
+        other.num_public_inputs += 1;
+        assert_ne!(build(10).content_digest(), other.content_digest());
+        // Enough multiplications to need more rows.
+        assert_ne!(build(10).content_digest(), build(1000).content_digest());
+    }
+
+    #[test]
+    fn test_check_witness() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        let z = builder.mul(x, y);
+        builder.register_public_input(z);
+        // `PublicInputGate` has no generators, so the wires of an extra instance can be set to
+        // anything. Its constraints require them to hold the public inputs hash.
+        let row = builder.add_gate(PublicInputGate, vec![]);
+        let data = builder.build::<C>();
+        let public_inputs_hash =
+            <C as GenericConfig<D>>::InnerHasher::hash_no_pad(&[F::from_canonical_u64(15)]);
+
+        let witness = |offsets: [u64; 4]| {
+            let mut pw = PartialWitness::new();
+            pw.set_target(x, F::from_canonical_u64(3));
+            pw.set_target(y, F::from_canonical_u64(5));
+            for (i, offset) in offsets.into_iter().enumerate() {
+                let value = public_inputs_hash.elements[i] + F::from_canonical_u64(offset);
+                pw.set_target(Target::wire(row, i), value);
+            }
+            generate_partial_witness(pw, &data.prover_only, &data.common)
+        };
+
+        assert_eq!(data.check_witness(&witness([0; 4])), Ok(()));
+
+        let failures = data.check_witness(&witness([1, 1, 0, 0])).unwrap_err();
+        let gate = <PublicInputGate as Gate<F, D>>::id(&PublicInputGate);
+        assert_eq!(
+            failures,
+            vec![
+                ConstraintFailure {
+                    row,
+                    gate: gate.clone(),
+                    constraint: 0,
+                },
+                ConstraintFailure {
+                    row,
+                    gate,
+                    constraint: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_copy_constraints() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        let unconnected = builder.add_virtual_target();
+        let row = builder.add_gate(ConstantGate::new(1), vec![F::ONE]);
+        let wire = Target::wire(row, 0);
+        builder.connect(x, y);
+        builder.connect(y, wire);
+        let data = builder.build::<C>();
+
+        let classes = data.prover_only.copy_constraints(&data.common);
+        let x_classes = classes
+            .iter()
+            .filter(|class| class.contains(&x))
+            .collect::<Vec<_>>();
+        assert_eq!(x_classes.len(), 1);
+        assert!(x_classes[0].contains(&y));
+        assert!(x_classes[0].contains(&wire));
+
+        assert!(classes.iter().all(|class| class.len() >= 2));
+        assert!(classes.iter().all(|class| !class.contains(&unconnected)));
+        let num_targets = classes.iter().map(Vec::len).sum::<usize>();
+        let distinct_targets = classes.iter().flatten().collect::<HashSet<_>>();
+        assert_eq!(distinct_targets.len(), num_targets);
+    }
+
+    #[test]
+    fn test_serialized_generator_watch_index() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        let xy = builder.mul(x, y);
+        let sum = builder.add(xy, x);
+        let inv = builder.inverse(sum);
+        builder.register_public_input(inv);
+        let data = builder.build::<C>();
+
+        let generator_serializer = DefaultGeneratorSerializer::<C, D>::default();
+        let bytes = data
+            .prover_only
+            .to_bytes(&generator_serializer, &data.common)
+            .unwrap();
+        let loaded = ProverOnlyCircuitData::<F, C, D>::from_bytes(
+            &bytes,
+            &generator_serializer,
+            &data.common,
+        )
+        .unwrap();
+        assert_eq!(
+            loaded.generator_indices_by_watches,
+            data.prover_only.generator_indices_by_watches
+        );
+
+        let witness = |prover_only: &ProverOnlyCircuitData<F, C, D>| {
+            let mut pw = PartialWitness::new();
+            pw.set_target(x, F::from_canonical_u64(3));
+            pw.set_target(y, F::from_canonical_u64(5));
+            // Fix the values which would otherwise be random, so that witnesses can be compared.
+            for &t in &prover_only.random_value_targets {
+                pw.set_target(t, F::ZERO);
+            }
+            generate_partial_witness(pw, prover_only, &data.common)
+                .full_witness()
+                .wire_values
+        };
+        assert_eq!(witness(&loaded), witness(&data.prover_only));
+    }
+}
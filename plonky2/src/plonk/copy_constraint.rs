@@ -4,7 +4,7 @@ use alloc::string::String;
 use crate::iop::target::Target;
 
 /// A named copy constraint.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct CopyConstraint {
     pub pair: (Target, Target),
     #[allow(dead_code)]
@@ -19,9 +19,11 @@ use crate::fri::proof::{
 use crate::fri::structure::{
     FriOpeningBatch, FriOpeningBatchTarget, FriOpenings, FriOpeningsTarget,
 };
+use crate::fri::verifier::fri_verify_proof_of_work;
 use crate::fri::FriParams;
 use crate::hash::hash_types::{MerkleCapTarget, RichField};
 use crate::hash::merkle_tree::MerkleCap;
+use crate::hash::poseidon::PoseidonHash;
 use crate::iop::ext_target::ExtensionTarget;
 use crate::iop::target::Target;
 use crate::plonk::circuit_data::{CommonCircuitData, VerifierOnlyCircuitData};
@@ -29,6 +31,9 @@ use crate::plonk::config::{GenericConfig, Hasher};
 use crate::plonk::verifier::verify_with_challenges;
 use crate::util::serialization::{Buffer, Read, Write};
 
+/// The version of the format written by `ProofWithPublicInputs::to_bytes_with_header`.
+pub const PROOF_FORMAT_VERSION: u32 = 1;
+
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
 #[serde(bound = "")]
 pub struct Proof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> {
@@ -107,6 +112,21 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
         C::InnerHasher::hash_no_pad(&self.public_inputs)
     }
 
+    /// Checks the FRI proof-of-work witness of this proof against the grinding difficulty of
+    /// `common_data`, without performing the rest of the verification.
+    pub fn verify_proof_of_work(
+        &self,
+        circuit_digest: &<<C as GenericConfig<D>>::Hasher as Hasher<C::F>>::Hash,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> anyhow::Result<()> {
+        let challenges =
+            self.get_challenges(self.get_public_inputs_hash(), circuit_digest, common_data)?;
+        fri_verify_proof_of_work(
+            challenges.fri_challenges.fri_pow_response,
+            &common_data.fri_params.config,
+        )
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut buffer = Vec::new();
         buffer
@@ -125,6 +145,55 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
             .map_err(anyhow::Error::msg)?;
         Ok(proof)
     }
+
+    /// Serializes the proof after a header made of `PROOF_FORMAT_VERSION`, the order of `F` and
+    /// the `content_digest` of `common_data`, so that receivers can check that the proof was made
+    /// for the circuit they expect before verifying it.
+    pub fn to_bytes_with_header(&self, common_data: &CommonCircuitData<F, D>) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        buffer
+            .write_u32(PROOF_FORMAT_VERSION)
+            .and_then(|_| buffer.write_all(&F::ORDER.to_le_bytes()))
+            .and_then(|_| buffer.write_hash::<F, PoseidonHash>(common_data.content_digest()))
+            .and_then(|_| buffer.write_proof_with_public_inputs(self))
+            .expect("Writing to a byte-vector cannot fail.");
+        buffer
+    }
+
+    /// Deserializes a proof written by `to_bytes_with_header`, failing if its header doesn't
+    /// match this format version, the field `F` or the shape of `common_data`.
+    pub fn from_bytes_with_header(
+        bytes: Vec<u8>,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> anyhow::Result<Self> {
+        let mut buffer = Buffer::new(&bytes);
+
+        let version = buffer.read_u32().map_err(anyhow::Error::msg)?;
+        ensure!(
+            version == PROOF_FORMAT_VERSION,
+            "Unsupported proof format version {version}, expected {PROOF_FORMAT_VERSION}"
+        );
+        let mut modulus = [0; 8];
+        buffer
+            .read_exact(&mut modulus)
+            .map_err(anyhow::Error::msg)?;
+        ensure!(
+            u64::from_le_bytes(modulus) == F::ORDER,
+            "Proof is over a different field"
+        );
+        let content_digest = buffer
+            .read_hash::<F, PoseidonHash>()
+            .map_err(anyhow::Error::msg)?;
+        ensure!(
+            content_digest == common_data.content_digest(),
+            "Proof was made for a different circuit"
+        );
+
+        let proof = buffer
+            .read_proof_with_public_inputs(common_data)
+            .map_err(anyhow::Error::msg)?;
+        Ok(proof)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
@@ -459,7 +528,9 @@ mod tests {
 
     use anyhow::Result;
     use itertools::Itertools;
-    use plonky2_field::types::Sample;
+    use plonky2_field::types::{Field, Sample};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
 
     use super::*;
     use crate::fri::reduction_strategies::FriReductionStrategy;
@@ -467,7 +538,7 @@ mod tests {
     use crate::gates::noop::NoopGate;
     use crate::iop::witness::PartialWitness;
     use crate::plonk::circuit_builder::CircuitBuilder;
-    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::circuit_data::{CircuitConfig, CircuitData};
     use crate::plonk::config::PoseidonGoldilocksConfig;
     use crate::plonk::verifier::verify;
 
@@ -509,6 +580,102 @@ mod tests {
         data.verify_compressed(compressed_proof)
     }
 
+    #[test]
+    fn test_proof_of_work_bits() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let build = |proof_of_work_bits| {
+            let mut config = CircuitConfig::standard_recursion_config();
+            config.fri_config = config.fri_config.with_pow_bits(proof_of_work_bits);
+            // Without grinding, the queries alone give 28 * 3 = 84 bits of security.
+            config.security_bits = 84;
+            let mut builder = CircuitBuilder::<F, D>::new(config);
+            let x = builder.constant(F::from_canonical_u64(3));
+            let y = builder.constant(F::from_canonical_u64(5));
+            builder.mul(x, y);
+            builder.build::<C>()
+        };
+        // Proofs are deterministic, so that the test doesn't depend on the luck of the grinding.
+        let prove = |data: &CircuitData<F, C, D>| {
+            data.prove_with_blinding_rng(PartialWitness::new(), &mut ChaCha8Rng::seed_from_u64(0))
+        };
+
+        let data = build(16);
+        assert_eq!(data.common.fri_params.config.proof_of_work_bits(), 16);
+        let proof = prove(&data)?;
+        proof.verify_proof_of_work(&data.verifier_only.circuit_digest, &data.common)?;
+        data.verify(proof)?;
+
+        // A proof ground with fewer bits is rejected by a verifier expecting 16 bits.
+        let weak_data = build(0);
+        let weak_proof = prove(&weak_data)?;
+        weak_data.verify(weak_proof.clone())?;
+        let mut common = weak_data.common.clone();
+        common.fri_params.config = common.fri_params.config.with_pow_bits(16);
+        assert!(weak_proof
+            .verify_proof_of_work(&weak_data.verifier_only.circuit_digest, &common)
+            .is_err());
+        assert!(verify(weak_proof, &weak_data.verifier_only, &common).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_proof_bytes_with_header() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let build = |num_noops| {
+            let config = CircuitConfig::standard_recursion_config();
+            let mut builder = CircuitBuilder::<F, D>::new(config);
+            let x = builder.constant(F::rand());
+            let y = builder.constant(F::rand());
+            let z = builder.mul(x, y);
+            builder.register_public_input(z);
+            for _ in 0..num_noops {
+                builder.add_gate(NoopGate, vec![]);
+            }
+            builder.build::<C>()
+        };
+
+        let data = build(10);
+        let proof = data.prove(PartialWitness::new())?;
+        let bytes = proof.to_bytes_with_header(&data.common);
+        let decoded =
+            ProofWithPublicInputs::<F, C, D>::from_bytes_with_header(bytes.clone(), &data.common)?;
+        assert_eq!(proof, decoded);
+        data.verify(decoded)?;
+
+        // A circuit of a different shape has a different content digest.
+        let other_data = build(1000);
+        assert!(ProofWithPublicInputs::<F, C, D>::from_bytes_with_header(
+            bytes.clone(),
+            &other_data.common
+        )
+        .is_err());
+
+        // Corrupting the digest in the header is detected too. It starts after the version and
+        // the modulus.
+        let mut corrupted = bytes.clone();
+        corrupted[12] ^= 1;
+        assert!(
+            ProofWithPublicInputs::<F, C, D>::from_bytes_with_header(corrupted, &data.common)
+                .is_err()
+        );
+
+        let mut corrupted = bytes;
+        corrupted[0] ^= 1;
+        assert!(
+            ProofWithPublicInputs::<F, C, D>::from_bytes_with_header(corrupted, &data.common)
+                .is_err()
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_proof_compression_lookup() -> Result<()> {
         const D: usize = 2;
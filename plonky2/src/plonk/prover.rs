@@ -1,16 +1,19 @@
 //! plonky2 prover implementation.
 
 #[cfg(not(feature = "std"))]
-use alloc::{format, vec, vec::Vec};
+use alloc::{format, string::String, vec, vec::Vec};
 use core::cmp::min;
+use core::fmt;
 use core::mem::swap;
 
-use anyhow::{ensure, Result};
+use anyhow::{bail, ensure, Result};
 use hashbrown::HashMap;
 use plonky2_maybe_rayon::*;
+use rand::rngs::OsRng;
+use rand::RngCore;
 
 use super::circuit_builder::{LookupChallenges, LookupWire};
-use crate::field::extension::Extendable;
+use crate::field::extension::{Extendable, FieldExtension};
 use crate::field::polynomial::{PolynomialCoeffs, PolynomialValues};
 use crate::field::types::Field;
 use crate::field::zero_poly_coset::ZeroPolyOnCoset;
@@ -19,7 +22,7 @@ use crate::gates::lookup::LookupGate;
 use crate::gates::lookup_table::LookupTableGate;
 use crate::gates::selectors::LookupSelectors;
 use crate::hash::hash_types::RichField;
-use crate::iop::challenger::Challenger;
+use crate::iop::challenger::{Challenger, TranscriptEntry};
 use crate::iop::generator::generate_partial_witness;
 use crate::iop::target::Target;
 use crate::iop::witness::{MatrixWitness, PartialWitness, PartitionWitness, Witness, WitnessWrite};
@@ -29,7 +32,7 @@ use crate::plonk::config::{GenericConfig, Hasher};
 use crate::plonk::plonk_common::PlonkOracle;
 use crate::plonk::proof::{OpeningSet, Proof, ProofWithPublicInputs};
 use crate::plonk::vanishing_poly::{eval_vanishing_poly_base_batch, get_lut_poly};
-use crate::plonk::vars::EvaluationVarsBaseBatch;
+use crate::plonk::vars::{EvaluationVars, EvaluationVarsBaseBatch};
 use crate::timed;
 use crate::util::partial_products::{partial_products_and_z_gx, quotient_chunk_products};
 use crate::util::timing::TimingTree;
@@ -128,6 +131,238 @@ where
     prove_with_partition_witness(prover_data, common_data, partition_witness, timing)
 }
 
+/// Like `prove`, but runs all of its parallel work in `pool` rather than in the global thread pool.
+/// A server proving several circuits at once can share one bounded pool between the jobs, instead
+/// of having each of them use every core.
+#[cfg(feature = "parallel")]
+pub fn prove_in_pool<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    inputs: PartialWitness<F>,
+    pool: &rayon::ThreadPool,
+    timing: &mut TimingTree,
+) -> Result<ProofWithPublicInputs<F, C, D>>
+where
+    C::Hasher: Hasher<F>,
+    C::InnerHasher: Hasher<F>,
+{
+    pool.install(|| prove(prover_data, common_data, inputs, timing))
+}
+
+/// Like `prove`, but also returns the generated witness, so that the values of intermediate targets
+/// can be inspected (e.g. with `Witness::get_targets`) when debugging a circuit.
+pub fn prove_and_return_witness<
+    'a,
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    prover_data: &'a ProverOnlyCircuitData<F, C, D>,
+    common_data: &'a CommonCircuitData<F, D>,
+    inputs: PartialWitness<F>,
+    timing: &mut TimingTree,
+) -> Result<(ProofWithPublicInputs<F, C, D>, PartitionWitness<'a, F>)>
+where
+    C::Hasher: Hasher<F>,
+    C::InnerHasher: Hasher<F>,
+{
+    let mut partition_witness = timed!(
+        timing,
+        &format!("run {} generators", prover_data.generators.len()),
+        generate_partial_witness(inputs, prover_data, common_data)
+    );
+    set_lookup_wires(prover_data, common_data, &mut partition_witness);
+
+    let proof = prove_with_rng_and_challenger(
+        prover_data,
+        common_data,
+        partition_witness.clone(),
+        &mut OsRng,
+        &mut Challenger::new(),
+        None,
+        timing,
+    )?;
+    Ok((proof, partition_witness))
+}
+
+/// Like `prove`, but first checks every gate constraint against the generated witness. If a
+/// constraint is not satisfied, an error naming the row, the gate and the constraint index is
+/// returned instead of an invalid proof. This is much slower than `prove`, so it should only be
+/// used to debug circuits.
+pub fn debug_prove<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    inputs: PartialWitness<F>,
+    timing: &mut TimingTree,
+) -> Result<ProofWithPublicInputs<F, C, D>>
+where
+    C::Hasher: Hasher<F>,
+    C::InnerHasher: Hasher<F>,
+{
+    let mut partition_witness = timed!(
+        timing,
+        &format!("run {} generators", prover_data.generators.len()),
+        generate_partial_witness(inputs, prover_data, common_data)
+    );
+    set_lookup_wires(prover_data, common_data, &mut partition_witness);
+
+    timed!(
+        timing,
+        "check gate constraints",
+        check_gate_constraints(prover_data, common_data, &partition_witness)?
+    );
+
+    prove_with_rng_and_challenger(
+        prover_data,
+        common_data,
+        partition_witness,
+        &mut OsRng,
+        &mut Challenger::new(),
+        None,
+        timing,
+    )
+}
+
+/// A gate constraint which is not satisfied by a witness.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintFailure {
+    pub row: usize,
+    /// The id of the gate whose constraint isn't satisfied.
+    pub gate: String,
+    /// The index of the constraint among the gate's constraints.
+    pub constraint: usize,
+}
+
+impl fmt::Display for ConstraintFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Constraint {} of gate {} is not satisfied at row {}",
+            self.constraint, self.gate, self.row
+        )
+    }
+}
+
+/// Returns an error describing the first gate constraint which isn't satisfied, if any.
+fn check_gate_constraints<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    partition_witness: &PartitionWitness<F>,
+) -> Result<()> {
+    let failures = gate_constraint_failures(prover_data, common_data, partition_witness);
+    if let Some(failure) = failures.first() {
+        bail!("{}", failure);
+    }
+    Ok(())
+}
+
+/// Evaluates the constraints of every gate at every row, and returns those which don't vanish,
+/// ordered by row.
+pub(crate) fn gate_constraint_failures<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    partition_witness: &PartitionWitness<F>,
+) -> Vec<ConstraintFailure> {
+    let public_inputs = partition_witness.get_targets(&prover_data.public_inputs);
+    let public_inputs_hash = C::InnerHasher::hash_no_pad(&public_inputs);
+    let wire_values = partition_witness.clone().full_witness().wire_values;
+    // The constant polynomials are committed in coefficient form, so we recover their values.
+    let constant_values: Vec<PolynomialValues<F>> =
+        prover_data.constants_sigmas_commitment.polynomials[common_data.constants_range()]
+            .iter()
+            .map(|poly| poly.clone().fft())
+            .collect();
+
+    let mut failures = Vec::new();
+    for row in 0..common_data.degree() {
+        let local_constants: Vec<F::Extension> = constant_values
+            .iter()
+            .map(|poly| F::Extension::from_basefield(poly.values[row]))
+            .collect();
+        let local_wires: Vec<F::Extension> = wire_values
+            .iter()
+            .map(|column| F::Extension::from_basefield(column[row]))
+            .collect();
+        let vars = EvaluationVars {
+            local_constants: &local_constants,
+            local_wires: &local_wires,
+            public_inputs_hash: &public_inputs_hash,
+        };
+
+        for (i, gate) in common_data.gates.iter().enumerate() {
+            let selector_index = common_data.selectors_info.selector_indices[i];
+            let constraints = gate.0.eval_filtered(
+                vars,
+                i,
+                selector_index,
+                common_data.selectors_info.groups[selector_index].clone(),
+                common_data.selectors_info.num_selectors(),
+                common_data.num_lookup_selectors,
+            );
+            for (j, constraint) in constraints.iter().enumerate() {
+                if !constraint.is_zero() {
+                    failures.push(ConstraintFailure {
+                        row,
+                        gate: gate.0.id(),
+                        constraint: j,
+                    });
+                }
+            }
+        }
+    }
+
+    failures
+}
+
+/// Like `prove`, but draws all blinding randomness (random witness values and Merkle leaf salts)
+/// from `rng`. Proving twice with identically seeded RNGs yields identical proofs, which is
+/// useful for testing; `prove` uses a CSPRNG.
+pub fn prove_with_blinding_rng<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    R: RngCore + ?Sized,
+    const D: usize,
+>(
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    mut inputs: PartialWitness<F>,
+    rng: &mut R,
+    timing: &mut TimingTree,
+) -> Result<ProofWithPublicInputs<F, C, D>>
+where
+    C::Hasher: Hasher<F>,
+    C::InnerHasher: Hasher<F>,
+{
+    for &target in &prover_data.random_value_targets {
+        inputs.set_target(target, F::sample(rng));
+    }
+
+    let mut partition_witness = timed!(
+        timing,
+        &format!("run {} generators", prover_data.generators.len()),
+        generate_partial_witness(inputs, prover_data, common_data)
+    );
+    set_lookup_wires(prover_data, common_data, &mut partition_witness);
+
+    prove_with_rng_and_challenger(
+        prover_data,
+        common_data,
+        partition_witness,
+        rng,
+        &mut Challenger::new(),
+        None,
+        timing,
+    )
+}
+
 pub fn prove_with_partition_witness<
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
@@ -138,6 +373,158 @@ pub fn prove_with_partition_witness<
     mut partition_witness: PartitionWitness<F>,
     timing: &mut TimingTree,
 ) -> Result<ProofWithPublicInputs<F, C, D>>
+where
+    C::Hasher: Hasher<F>,
+    C::InnerHasher: Hasher<F>,
+{
+    set_lookup_wires(prover_data, common_data, &mut partition_witness);
+
+    prove_with_rng_and_challenger(
+        prover_data,
+        common_data,
+        partition_witness,
+        &mut OsRng,
+        &mut Challenger::new(),
+        None,
+        timing,
+    )
+}
+
+/// Like `prove`, but also returns the log of every element observed and every challenge drawn by
+/// the Fiat-Shamir challenger, so that the transcript can be audited.
+#[allow(clippy::type_complexity)]
+pub fn prove_with_transcript_log<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    inputs: PartialWitness<F>,
+    timing: &mut TimingTree,
+) -> Result<(ProofWithPublicInputs<F, C, D>, Vec<TranscriptEntry<F>>)>
+where
+    C::Hasher: Hasher<F>,
+    C::InnerHasher: Hasher<F>,
+{
+    let mut partition_witness = timed!(
+        timing,
+        &format!("run {} generators", prover_data.generators.len()),
+        generate_partial_witness(inputs, prover_data, common_data)
+    );
+    set_lookup_wires(prover_data, common_data, &mut partition_witness);
+
+    let mut challenger = Challenger::new();
+    challenger.enable_transcript_log();
+    let proof = prove_with_rng_and_challenger(
+        prover_data,
+        common_data,
+        partition_witness,
+        &mut OsRng,
+        &mut challenger,
+        None,
+        timing,
+    )?;
+    let transcript_log = challenger.transcript_log().unwrap_or_default().to_vec();
+    Ok((proof, transcript_log))
+}
+
+/// Like `prove`, but calls `hook` on the generated witness before it is committed to, so that it
+/// can be inspected or adjusted. The hook must preserve the satisfaction of every constraint,
+/// otherwise the resulting proof won't verify. The lookup wires are set after the hook runs, so
+/// they account for any adjusted lookup inputs.
+pub fn prove_with_witness_hook<
+    'a,
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    prover_data: &'a ProverOnlyCircuitData<F, C, D>,
+    common_data: &'a CommonCircuitData<F, D>,
+    inputs: PartialWitness<F>,
+    hook: impl FnOnce(&mut PartitionWitness<'a, F>),
+    timing: &mut TimingTree,
+) -> Result<ProofWithPublicInputs<F, C, D>>
+where
+    C::Hasher: Hasher<F>,
+    C::InnerHasher: Hasher<F>,
+{
+    let mut partition_witness = timed!(
+        timing,
+        &format!("run {} generators", prover_data.generators.len()),
+        generate_partial_witness(inputs, prover_data, common_data)
+    );
+
+    hook(&mut partition_witness);
+
+    prove_with_partition_witness(prover_data, common_data, partition_witness, timing)
+}
+
+/// Receives progress updates from `prove_with_observer`, e.g. to drive a progress bar. Each
+/// callback is called once the corresponding step is done.
+pub trait ProverObserver {
+    fn witness_generated(&mut self) {}
+
+    /// Called once the wire polynomials are committed to.
+    fn committed_trace(&mut self) {}
+
+    /// Called once the quotient polynomials are computed and committed to.
+    fn computed_quotient(&mut self) {}
+
+    /// Called after each FRI folding step, with `fold` going from 1 to `num_folds`.
+    fn fri_fold(&mut self, _fold: usize, _num_folds: usize) {}
+}
+
+/// Like `prove`, but reports its progress to `observer`.
+pub fn prove_with_observer<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    inputs: PartialWitness<F>,
+    observer: &mut dyn ProverObserver,
+    timing: &mut TimingTree,
+) -> Result<ProofWithPublicInputs<F, C, D>>
+where
+    C::Hasher: Hasher<F>,
+    C::InnerHasher: Hasher<F>,
+{
+    let mut partition_witness = timed!(
+        timing,
+        &format!("run {} generators", prover_data.generators.len()),
+        generate_partial_witness(inputs, prover_data, common_data)
+    );
+    set_lookup_wires(prover_data, common_data, &mut partition_witness);
+    observer.witness_generated();
+
+    prove_with_rng_and_challenger(
+        prover_data,
+        common_data,
+        partition_witness,
+        &mut OsRng,
+        &mut Challenger::new(),
+        Some(observer),
+        timing,
+    )
+}
+
+/// Proves with a witness whose lookup wires were already set by `set_lookup_wires`.
+fn prove_with_rng_and_challenger<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    R: RngCore + ?Sized,
+    const D: usize,
+>(
+    prover_data: &ProverOnlyCircuitData<F, C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    partition_witness: PartitionWitness<F>,
+    rng: &mut R,
+    challenger: &mut Challenger<F, C::Hasher>,
+    mut observer: Option<&mut dyn ProverObserver>,
+    timing: &mut TimingTree,
+) -> Result<ProofWithPublicInputs<F, C, D>>
 where
     C::Hasher: Hasher<F>,
     C::InnerHasher: Hasher<F>,
@@ -148,8 +535,6 @@ where
     let quotient_degree = common_data.quotient_degree();
     let degree = common_data.degree();
 
-    set_lookup_wires(prover_data, common_data, &mut partition_witness);
-
     let public_inputs = partition_witness.get_targets(&prover_data.public_inputs);
     let public_inputs_hash = C::InnerHasher::hash_no_pad(&public_inputs);
 
@@ -172,17 +557,20 @@ where
     let wires_commitment = timed!(
         timing,
         "compute wires commitment",
-        PolynomialBatch::<F, C, D>::from_values(
+        PolynomialBatch::<F, C, D>::from_values_with_rng(
             wires_values,
             config.fri_config.rate_bits,
             config.zero_knowledge && PlonkOracle::WIRES.blinding,
             config.fri_config.cap_height,
             timing,
             prover_data.fft_root_table.as_ref(),
+            rng,
         )
     );
 
-    let mut challenger = Challenger::<F, C::Hasher>::new();
+    if let Some(observer) = observer.as_deref_mut() {
+        observer.committed_trace();
+    }
 
     // Observe the instance.
     challenger.observe_hash::<C::Hasher>(prover_data.circuit_digest);
@@ -239,13 +627,14 @@ where
     let partial_products_zs_and_lookup_commitment = timed!(
         timing,
         "commit to partial products, Z's and, if any, lookup polynomials",
-        PolynomialBatch::from_values(
+        PolynomialBatch::from_values_with_rng(
             zs_partial_products_lookups,
             config.fri_config.rate_bits,
             config.zero_knowledge && PlonkOracle::ZS_PARTIAL_PRODUCTS.blinding,
             config.fri_config.cap_height,
             timing,
             prover_data.fft_root_table.as_ref(),
+            rng,
         )
     );
 
@@ -287,18 +676,23 @@ where
     let quotient_polys_commitment = timed!(
         timing,
         "commit to quotient polys",
-        PolynomialBatch::<F, C, D>::from_coeffs(
+        PolynomialBatch::<F, C, D>::from_coeffs_with_rng(
             all_quotient_poly_chunks,
             config.fri_config.rate_bits,
             config.zero_knowledge && PlonkOracle::QUOTIENT.blinding,
             config.fri_config.cap_height,
             timing,
             prover_data.fft_root_table.as_ref(),
+            rng,
         )
     );
 
     challenger.observe_cap::<C::Hasher>(&quotient_polys_commitment.merkle_tree.cap);
 
+    if let Some(observer) = observer.as_deref_mut() {
+        observer.computed_quotient();
+    }
+
     let zeta = challenger.get_extension_challenge::<D>();
     // To avoid leaking witness data, we want to ensure that our opening locations, `zeta` and
     // `g * zeta`, are not in our subgroup `H`. It suffices to check `zeta` only, since
@@ -328,7 +722,7 @@ where
     let opening_proof = timed!(
         timing,
         "compute opening proofs",
-        PolynomialBatch::<F, C, D>::prove_openings(
+        PolynomialBatch::<F, C, D>::prove_openings_with_observer(
             &instance,
             &[
                 &prover_data.constants_sigmas_commitment,
@@ -336,8 +730,9 @@ where
                 &partial_products_zs_and_lookup_commitment,
                 &quotient_polys_commitment,
             ],
-            &mut challenger,
+            challenger,
             &common_data.fri_params,
+            observer,
             timing,
         )
     );
@@ -0,0 +1,267 @@
+//! Symbolic forms of gate constraints, which can be exported to check them with external tools.
+
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+use hashbrown::HashMap;
+
+use crate::field::extension::{Extendable, FieldExtension};
+use crate::field::types::Field;
+use crate::gadgets::arithmetic_extension::ExtensionArithmeticOperation;
+use crate::gates::gate::GateRef;
+use crate::hash::hash_types::RichField;
+use crate::iop::ext_target::ExtensionTarget;
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::CircuitConfig;
+use crate::plonk::vars::{EvaluationTargets, EvaluationVars};
+
+/// A polynomial over the values of a row, with coefficients in `F`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SymbolicExpr<F: Field> {
+    Constant(F),
+    /// The value of the wire in the given column of the row.
+    Wire(usize),
+    /// The given constant of the gate, not counting the selectors.
+    GateConstant(usize),
+    /// The given element of the hash of the public inputs.
+    PublicInputsHash(usize),
+    Sum(Box<Self>, Box<Self>),
+    Product(Box<Self>, Box<Self>),
+}
+
+impl<F: Field> SymbolicExpr<F> {
+    /// Returns `a + b`, folding constants.
+    pub fn sum(a: Self, b: Self) -> Self {
+        match (a, b) {
+            (Self::Constant(x), Self::Constant(y)) => Self::Constant(x + y),
+            (Self::Constant(x), e) | (e, Self::Constant(x)) if x.is_zero() => e,
+            (a, b) => Self::Sum(Box::new(a), Box::new(b)),
+        }
+    }
+
+    /// Returns `a * b`, folding constants.
+    pub fn product(a: Self, b: Self) -> Self {
+        match (a, b) {
+            (Self::Constant(x), Self::Constant(y)) => Self::Constant(x * y),
+            (Self::Constant(x), _) | (_, Self::Constant(x)) if x.is_zero() => {
+                Self::Constant(F::ZERO)
+            }
+            (Self::Constant(x), e) | (e, Self::Constant(x)) if x.is_one() => e,
+            (a, b) => Self::Product(Box::new(a), Box::new(b)),
+        }
+    }
+
+    /// Evaluates the expression on the values of a row.
+    pub fn eval<B, const D: usize>(&self, vars: &EvaluationVars<B, D>) -> F
+    where
+        B: RichField + Extendable<D, Extension = F>,
+        F: FieldExtension<D, BaseField = B>,
+    {
+        match self {
+            Self::Constant(c) => *c,
+            Self::Wire(i) => vars.local_wires[*i],
+            Self::GateConstant(i) => vars.local_constants[*i],
+            Self::PublicInputsHash(i) => {
+                <F as FieldExtension<D>>::from_basefield(vars.public_inputs_hash.elements[*i])
+            }
+            Self::Sum(a, b) => a.eval(vars) + b.eval(vars),
+            Self::Product(a, b) => a.eval(vars) * b.eval(vars),
+        }
+    }
+}
+
+/// A constraint of a gate, which must vanish on every row using the gate.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SymbolicConstraint<F: Field> {
+    /// The id of the gate.
+    pub gate_id: String,
+    /// The index of the constraint among those of the gate.
+    pub index: usize,
+    pub expr: SymbolicExpr<F>,
+}
+
+/// Returns the constraints of `gate` in symbolic form. They are obtained by evaluating the gate's
+/// recursive constraints in a scratch circuit, and reading back the arithmetic operations which
+/// were added. Panics if the gate uses an operation other than `arithmetic_extension`.
+pub(crate) fn gate_constraints<F: RichField + Extendable<D>, const D: usize>(
+    gate: &GateRef<F, D>,
+    config: &CircuitConfig,
+) -> Vec<SymbolicConstraint<F::Extension>> {
+    // Some gadgets, like the Poseidon MDS layer, use auxiliary gates when there are enough routed
+    // wires. With room for a single arithmetic operation per gate, they use plain arithmetic.
+    let config = CircuitConfig {
+        num_routed_wires: 4 * D,
+        ..config.clone()
+    };
+    let num_wires = config.num_wires;
+    let mut builder = CircuitBuilder::<F, D>::new(config);
+
+    let local_constants = builder.add_virtual_extension_targets(gate.0.num_constants());
+    let local_wires = builder.add_virtual_extension_targets(num_wires);
+    let public_inputs_hash = builder.add_virtual_hash();
+    let constraints = gate.0.eval_unfiltered_circuit(
+        &mut builder,
+        EvaluationTargets {
+            local_constants: &local_constants,
+            local_wires: &local_wires,
+            public_inputs_hash: &public_inputs_hash,
+        },
+    );
+
+    let mut exprs = HashMap::new();
+    for (i, &t) in local_constants.iter().enumerate() {
+        exprs.insert(t, SymbolicExpr::GateConstant(i));
+    }
+    for (i, &t) in local_wires.iter().enumerate() {
+        exprs.insert(t, SymbolicExpr::Wire(i));
+    }
+    for (i, &t) in public_inputs_hash.elements.iter().enumerate() {
+        exprs.insert(builder.convert_to_ext(t), SymbolicExpr::PublicInputsHash(i));
+    }
+
+    let operations = builder
+        .arithmetic_results
+        .iter()
+        .map(|(&operation, &result)| (result, operation))
+        .collect::<HashMap<_, _>>();
+
+    let gate_id = gate.0.id();
+    constraints
+        .into_iter()
+        .enumerate()
+        .map(|(index, constraint)| SymbolicConstraint {
+            gate_id: gate_id.clone(),
+            index,
+            expr: to_symbolic(constraint, &builder, &operations, &mut exprs, &gate_id),
+        })
+        .collect()
+}
+
+fn to_symbolic<F: RichField + Extendable<D>, const D: usize>(
+    target: ExtensionTarget<D>,
+    builder: &CircuitBuilder<F, D>,
+    operations: &HashMap<ExtensionTarget<D>, ExtensionArithmeticOperation<F, D>>,
+    exprs: &mut HashMap<ExtensionTarget<D>, SymbolicExpr<F::Extension>>,
+    gate_id: &str,
+) -> SymbolicExpr<F::Extension> {
+    if let Some(expr) = exprs.get(&target) {
+        return expr.clone();
+    }
+    if let Some(c) = builder.target_as_constant_ext(target) {
+        return SymbolicExpr::Constant(c);
+    }
+
+    let operation = operations.get(&target).unwrap_or_else(|| {
+        panic!("{gate_id} uses an operation which can't be exported symbolically")
+    });
+    let mut operand = |t| to_symbolic(t, builder, operations, exprs, gate_id);
+    let multiplicand_0 = operand(operation.multiplicand_0);
+    let multiplicand_1 = operand(operation.multiplicand_1);
+    let addend = operand(operation.addend);
+
+    // `const_0 * multiplicand_0 * multiplicand_1 + const_1 * addend`.
+    let product = SymbolicExpr::product(
+        SymbolicExpr::product(
+            SymbolicExpr::Constant(operation.const_0.into()),
+            multiplicand_0,
+        ),
+        multiplicand_1,
+    );
+    let scaled_addend =
+        SymbolicExpr::product(SymbolicExpr::Constant(operation.const_1.into()), addend);
+    let expr = SymbolicExpr::sum(product, scaled_addend);
+
+    exprs.insert(target, expr.clone());
+    expr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::types::Sample;
+    use crate::gates::arithmetic_base::ArithmeticGate;
+    use crate::hash::hash_types::HashOut;
+    use crate::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type FE = <F as Extendable<D>>::Extension;
+
+    fn test_circuit() -> crate::plonk::circuit_data::CircuitData<F, C, D> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        let xy = builder.mul(x, y);
+        let z = builder.add_const(xy, F::from_canonical_u64(7));
+        builder.register_public_input(z);
+        builder.build::<C>()
+    }
+
+    #[test]
+    fn test_export_arithmetic_gate() {
+        let data = test_circuit();
+        let constraints = data.common.export_constraints();
+        let gate_id = data
+            .common
+            .gates
+            .iter()
+            .map(|gate| gate.0.id())
+            .find(|id| id.starts_with("ArithmeticGate"))
+            .expect("The circuit has an arithmetic gate");
+
+        // `output - (const_0 * multiplicand_0 * multiplicand_1 + const_1 * addend)`.
+        let constraint = constraints
+            .iter()
+            .find(|c| c.gate_id == gate_id && c.index == 0)
+            .unwrap();
+        let wire = |i| Box::new(SymbolicExpr::Wire(i));
+        let computed_output = SymbolicExpr::Sum(
+            Box::new(SymbolicExpr::Product(
+                Box::new(SymbolicExpr::GateConstant(1)),
+                wire(ArithmeticGate::wire_ith_addend(0)),
+            )),
+            Box::new(SymbolicExpr::Product(
+                Box::new(SymbolicExpr::Product(
+                    Box::new(SymbolicExpr::GateConstant(0)),
+                    wire(ArithmeticGate::wire_ith_multiplicand_0(0)),
+                )),
+                wire(ArithmeticGate::wire_ith_multiplicand_1(0)),
+            )),
+        );
+        let expected = SymbolicExpr::Sum(
+            wire(ArithmeticGate::wire_ith_output(0)),
+            Box::new(SymbolicExpr::Product(
+                Box::new(SymbolicExpr::Constant(FE::NEG_ONE)),
+                Box::new(computed_output),
+            )),
+        );
+        assert_eq!(constraint.expr, expected);
+    }
+
+    #[test]
+    fn test_export_constraints_eval() {
+        let data = test_circuit();
+        let constraints = data.common.export_constraints();
+
+        for gate in &data.common.gates {
+            let local_constants = FE::rand_vec(gate.0.num_constants());
+            let local_wires = FE::rand_vec(data.common.config.num_wires);
+            let public_inputs_hash = HashOut::rand();
+            let vars = EvaluationVars {
+                local_constants: &local_constants,
+                local_wires: &local_wires,
+                public_inputs_hash: &public_inputs_hash,
+            };
+
+            let expected = gate.0.eval_unfiltered(vars);
+            let exported = constraints
+                .iter()
+                .filter(|c| c.gate_id == gate.0.id())
+                .map(|c| c.expr.eval(&vars))
+                .collect::<Vec<_>>();
+            assert_eq!(exported, expected, "Mismatch for {}", gate.0.id());
+        }
+    }
+}
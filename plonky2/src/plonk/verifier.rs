@@ -1,10 +1,16 @@
 //! plonky2 verifier implementation.
 
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
 use anyhow::{ensure, Result};
+use rand::seq::SliceRandom;
+use rand::RngCore;
 
 use crate::field::extension::Extendable;
 use crate::field::types::Field;
-use crate::fri::verifier::verify_fri_proof;
+use crate::fri::verifier::verify_fri_proof_queries;
+use crate::fri::FriConfig;
 use crate::hash::hash_types::RichField;
 use crate::plonk::circuit_data::{CommonCircuitData, VerifierOnlyCircuitData};
 use crate::plonk::config::{GenericConfig, Hasher};
@@ -14,7 +20,13 @@ use crate::plonk::validate_shape::validate_proof_with_pis_shape;
 use crate::plonk::vanishing_poly::eval_vanishing_poly;
 use crate::plonk::vars::EvaluationVars;
 
-pub(crate) fn verify<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
+/// Verifies `proof_with_pis` using only verifier-side circuit data.
+///
+/// A verifier only needs to persist the circuit's [`VerifierOnlyCircuitData`] (the constants and
+/// sigmas cap and the circuit digest) and its [`CommonCircuitData`]; the prover-only data, which
+/// includes the witness generators, is not required. Both can be serialized with their `to_bytes`
+/// methods.
+pub fn verify<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
     proof_with_pis: ProofWithPublicInputs<F, C, D>,
     verifier_data: &VerifierOnlyCircuitData<C, D>,
     common_data: &CommonCircuitData<F, D>,
@@ -37,6 +49,78 @@ pub(crate) fn verify<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, c
     )
 }
 
+/// Verifies `proof_with_pis` after checking that the hash of its public inputs matches
+/// `expected_public_inputs_hash`. The hash comparison is done before any other verification
+/// work, so proofs for the wrong public inputs are rejected early.
+pub(crate) fn verify_with_public_inputs_hash<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    proof_with_pis: ProofWithPublicInputs<F, C, D>,
+    expected_public_inputs_hash: <<C as GenericConfig<D>>::InnerHasher as Hasher<F>>::Hash,
+    verifier_data: &VerifierOnlyCircuitData<C, D>,
+    common_data: &CommonCircuitData<F, D>,
+) -> Result<()> {
+    ensure!(
+        proof_with_pis.get_public_inputs_hash() == expected_public_inputs_hash,
+        "Public inputs hash does not match the expected hash"
+    );
+    verify::<F, C, D>(proof_with_pis, verifier_data, common_data)
+}
+
+/// Checks `proof_with_pis` like `verify`, except that only `num_queries` of the FRI query rounds,
+/// chosen at random with `rng`, are checked. Returns the conjectured security of this check in
+/// bits, which is lower than that of full verification unless all the query rounds are checked.
+///
+/// This is *not* a full verification: a proof which passes it may still be invalid. It is meant
+/// for cheaply spot-checking proofs, e.g. from a batch which will be fully verified later.
+pub fn verify_sampled<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+    R: RngCore + ?Sized,
+>(
+    proof_with_pis: ProofWithPublicInputs<F, C, D>,
+    verifier_data: &VerifierOnlyCircuitData<C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    num_queries: usize,
+    rng: &mut R,
+) -> Result<f64> {
+    let fri_config = &common_data.fri_params.config;
+    ensure!(
+        num_queries <= fri_config.num_query_rounds,
+        "Cannot sample {num_queries} of {} query rounds",
+        fri_config.num_query_rounds
+    );
+
+    validate_proof_with_pis_shape(&proof_with_pis, common_data)?;
+
+    let public_inputs_hash = proof_with_pis.get_public_inputs_hash();
+    let challenges = proof_with_pis.get_challenges(
+        public_inputs_hash,
+        &verifier_data.circuit_digest,
+        common_data,
+    )?;
+
+    let mut query_rounds = (0..fri_config.num_query_rounds).collect::<Vec<_>>();
+    let (query_rounds, _) = query_rounds.partial_shuffle(rng, num_queries);
+    verify_with_challenges_and_queries::<F, C, D>(
+        proof_with_pis.proof,
+        public_inputs_hash,
+        challenges,
+        verifier_data,
+        common_data,
+        query_rounds,
+    )?;
+
+    let sampled_config = FriConfig {
+        num_query_rounds: num_queries,
+        ..fri_config.clone()
+    };
+    Ok(sampled_config.conjectured_security_bits::<F::Extension>(common_data.degree_bits()))
+}
+
 pub(crate) fn verify_with_challenges<
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
@@ -47,6 +131,30 @@ pub(crate) fn verify_with_challenges<
     challenges: ProofChallenges<F, D>,
     verifier_data: &VerifierOnlyCircuitData<C, D>,
     common_data: &CommonCircuitData<F, D>,
+) -> Result<()> {
+    let query_rounds = (0..common_data.fri_params.config.num_query_rounds).collect::<Vec<_>>();
+    verify_with_challenges_and_queries::<F, C, D>(
+        proof,
+        public_inputs_hash,
+        challenges,
+        verifier_data,
+        common_data,
+        &query_rounds,
+    )
+}
+
+/// Like `verify_with_challenges`, but only checks the FRI query rounds in `query_rounds`.
+fn verify_with_challenges_and_queries<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    proof: Proof<F, C, D>,
+    public_inputs_hash: <<C as GenericConfig<D>>::InnerHasher as Hasher<F>>::Hash,
+    challenges: ProofChallenges<F, D>,
+    verifier_data: &VerifierOnlyCircuitData<C, D>,
+    common_data: &CommonCircuitData<F, D>,
+    query_rounds: &[usize],
 ) -> Result<()> {
     let local_constants = &proof.openings.constants;
     let local_wires = &proof.openings.wires;
@@ -105,14 +213,154 @@ pub(crate) fn verify_with_challenges<
         proof.quotient_polys_cap,
     ];
 
-    verify_fri_proof::<F, C, D>(
+    verify_fri_proof_queries::<F, C, D>(
         &common_data.get_fri_instance(challenges.plonk_zeta),
         &proof.openings.to_fri_openings(),
         &challenges.fri_challenges,
         merkle_caps,
         &proof.opening_proof,
         &common_data.fri_params,
+        query_rounds,
     )?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::PoseidonGoldilocksConfig;
+    use crate::util::serialization::DefaultGateSerializer;
+
+    #[test]
+    fn test_verify_with_public_inputs_hash() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.add_virtual_target();
+        let y = builder.square(x);
+        builder.register_public_input(x);
+        builder.register_public_input(y);
+        pw.set_target(x, F::from_canonical_u64(3));
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+
+        let expected_hash = <C as GenericConfig<D>>::InnerHasher::hash_no_pad(&[
+            F::from_canonical_u64(3),
+            F::from_canonical_u64(9),
+        ]);
+        data.verify_with_public_inputs_hash(proof.clone(), expected_hash)?;
+
+        let wrong_hash = <C as GenericConfig<D>>::InnerHasher::hash_no_pad(&[
+            F::from_canonical_u64(3),
+            F::from_canonical_u64(10),
+        ]);
+        assert!(data
+            .verify_with_public_inputs_hash(proof, wrong_hash)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_sampled() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.add_virtual_target();
+        let y = builder.square(x);
+        builder.register_public_input(y);
+        pw.set_target(x, F::from_canonical_u64(7));
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw)?;
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+        // 5 queries at rate 1/8, plus 16 bits of grinding.
+        let security_bits = verify_sampled(
+            proof.clone(),
+            &data.verifier_only,
+            &data.common,
+            5,
+            &mut rng,
+        )?;
+        assert_eq!(security_bits, 31.0);
+        let num_query_rounds = data.common.fri_params.config.num_query_rounds;
+        let security_bits = verify_sampled(
+            proof.clone(),
+            &data.verifier_only,
+            &data.common,
+            num_query_rounds,
+            &mut rng,
+        )?;
+        assert_eq!(security_bits, 100.0);
+        assert!(verify_sampled(
+            proof.clone(),
+            &data.verifier_only,
+            &data.common,
+            num_query_rounds + 1,
+            &mut rng
+        )
+        .is_err());
+
+        // Tampering with a single query round is only caught if that round is sampled, so tamper
+        // with all of them.
+        let mut tampered = proof;
+        for round in &mut tampered.proof.opening_proof.query_round_proofs {
+            round.initial_trees_proof.evals_proofs[0].0[0] += F::ONE;
+        }
+        assert!(verify_sampled(tampered, &data.verifier_only, &data.common, 5, &mut rng).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_with_deserialized_verifier_data() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.add_virtual_target();
+        let y = builder.cube(x);
+        builder.register_public_input(y);
+        pw.set_target(x, F::from_canonical_u64(4));
+
+        let data = builder.build::<C>();
+        let proof_bytes = data.prove(pw)?.to_bytes();
+
+        let gate_serializer = DefaultGateSerializer;
+        let verifier_only_bytes = data.verifier_only.to_bytes().unwrap();
+        let common_bytes = data.common.to_bytes(&gate_serializer).unwrap();
+        drop(data);
+
+        let verifier_only =
+            VerifierOnlyCircuitData::<C, D>::from_bytes(verifier_only_bytes).unwrap();
+        let common = CommonCircuitData::<F, D>::from_bytes(common_bytes, &gate_serializer).unwrap();
+        let proof = ProofWithPublicInputs::<F, C, D>::from_bytes(proof_bytes, &common)?;
+        assert_eq!(proof.public_inputs, vec![F::from_canonical_u64(64)]);
+
+        verify(proof, &verifier_only, &common)
+    }
+}
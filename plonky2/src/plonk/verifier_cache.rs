@@ -0,0 +1,161 @@
+//! A cache of deserialized verifier data, for services verifying many proofs of the same circuits.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use anyhow::{anyhow, Result};
+use hashbrown::HashMap;
+
+use crate::field::extension::Extendable;
+use crate::hash::hash_types::RichField;
+use crate::plonk::circuit_data::VerifierCircuitData;
+use crate::plonk::config::{GenericConfig, GenericHashOut, Hasher};
+use crate::plonk::proof::ProofWithPublicInputs;
+use crate::util::serialization::{GateSerializer, IoError, IoResult};
+
+type CircuitDigest<F, C, const D: usize> = <<C as GenericConfig<D>>::Hasher as Hasher<F>>::Hash;
+
+/// Holds deserialized `VerifierCircuitData`, keyed by circuit digest, so that the verifier data of
+/// a circuit is only parsed once no matter how many of its proofs are verified.
+///
+/// The cache does no internal locking. Lookups and verification only need `&self`, so a cache
+/// which is filled upfront can be shared between threads as is; one which is filled while serving
+/// requests should be wrapped in an `RwLock`.
+#[derive(Debug)]
+pub struct VerifierCache<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> {
+    circuits: HashMap<Vec<u8>, VerifierCircuitData<F, C, D>>,
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
+    VerifierCache<F, C, D>
+{
+    pub fn new() -> Self {
+        Self {
+            circuits: HashMap::new(),
+        }
+    }
+
+    /// Adds already deserialized verifier data to the cache.
+    pub fn insert(&mut self, data: VerifierCircuitData<F, C, D>) {
+        let key = data.verifier_only.circuit_digest.to_bytes();
+        self.circuits.insert(key, data);
+    }
+
+    pub fn get(
+        &self,
+        circuit_digest: &CircuitDigest<F, C, D>,
+    ) -> Option<&VerifierCircuitData<F, C, D>> {
+        self.circuits.get(&circuit_digest.to_bytes())
+    }
+
+    /// Returns the verifier data of the circuit with the given digest, deserializing it from
+    /// `bytes` only if it isn't cached yet. Fails if `bytes` belong to another circuit.
+    pub fn get_or_load(
+        &mut self,
+        circuit_digest: &CircuitDigest<F, C, D>,
+        bytes: &[u8],
+        gate_serializer: &dyn GateSerializer<F, D>,
+    ) -> IoResult<&VerifierCircuitData<F, C, D>> {
+        let key = circuit_digest.to_bytes();
+        if !self.circuits.contains_key(&key) {
+            let data = VerifierCircuitData::from_bytes(bytes.to_vec(), gate_serializer)?;
+            if data.verifier_only.circuit_digest != *circuit_digest {
+                log::error!("Verifier data doesn't match the requested circuit digest");
+                return Err(IoError);
+            }
+            self.circuits.insert(key.clone(), data);
+        }
+        Ok(&self.circuits[&key])
+    }
+
+    /// Verifies a proof of the cached circuit with the given digest.
+    pub fn verify(
+        &self,
+        circuit_digest: &CircuitDigest<F, C, D>,
+        proof_with_pis: ProofWithPublicInputs<F, C, D>,
+    ) -> Result<()> {
+        self.get(circuit_digest)
+            .ok_or_else(|| anyhow!("No verifier data cached for this circuit digest"))?
+            .verify(proof_with_pis)
+    }
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> Default
+    for VerifierCache<F, C, D>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::types::Field;
+    use crate::iop::witness::{PartialWitness, WitnessWrite};
+    use crate::plonk::circuit_builder::CircuitBuilder;
+    use crate::plonk::circuit_data::CircuitConfig;
+    use crate::plonk::config::PoseidonGoldilocksConfig;
+    use crate::util::serialization::DefaultGateSerializer;
+
+    #[test]
+    fn test_verifier_cache() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        let z = builder.mul(x, y);
+        builder.register_public_input(z);
+        let data = builder.build::<C>();
+
+        let proof = |a: u64, b: u64| {
+            let mut pw = PartialWitness::new();
+            pw.set_target(x, F::from_canonical_u64(a));
+            pw.set_target(y, F::from_canonical_u64(b));
+            data.prove(pw)
+        };
+
+        let gate_serializer = DefaultGateSerializer;
+        let bytes = data.verifier_data().to_bytes(&gate_serializer).unwrap();
+        let digest = data.verifier_only.circuit_digest;
+
+        let mut cache = VerifierCache::<F, C, D>::new();
+        assert!(cache.get(&digest).is_none());
+        assert!(cache.verify(&digest, proof(3, 5)?).is_err());
+
+        let loaded = cache
+            .get_or_load(&digest, &bytes, &gate_serializer)
+            .unwrap();
+        assert_eq!(loaded.verifier_only, data.verifier_only);
+        assert_eq!(loaded.common, data.common);
+        // Cached data is returned without looking at the bytes again.
+        assert!(cache.get_or_load(&digest, &[], &gate_serializer).is_ok());
+
+        for (a, b) in [(3, 5), (7, 11)] {
+            let proof = proof(a, b)?;
+            data.verify(proof.clone())?;
+            cache.verify(&digest, proof)?;
+        }
+
+        // Verifier data for another circuit is rejected.
+        let mut other_builder =
+            CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let t = other_builder.add_virtual_target();
+        other_builder.square(t);
+        let other_bytes = other_builder
+            .build::<C>()
+            .verifier_data()
+            .to_bytes(&gate_serializer)
+            .unwrap();
+        let mut other_cache = VerifierCache::<F, C, D>::new();
+        assert!(other_cache
+            .get_or_load(&digest, &other_bytes, &gate_serializer)
+            .is_err());
+
+        Ok(())
+    }
+}
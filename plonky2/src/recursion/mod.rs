@@ -8,3 +8,4 @@ pub mod conditional_recursive_verifier;
 pub mod cyclic_recursion;
 pub mod dummy_circuit;
 pub mod recursive_verifier;
+pub mod tree_aggregation;
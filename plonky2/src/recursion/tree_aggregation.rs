@@ -0,0 +1,155 @@
+//! Aggregation of many proofs of the same circuit into a single proof, using a balanced binary
+//! tree of recursive verifiers.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use anyhow::{ensure, Result};
+
+use crate::field::extension::Extendable;
+use crate::hash::hash_types::RichField;
+use crate::iop::witness::{PartialWitness, WitnessWrite};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::circuit_data::{CircuitConfig, CircuitData, VerifierCircuitData};
+use crate::plonk::config::{AlgebraicHasher, GenericConfig};
+use crate::plonk::proof::{ProofWithPublicInputs, ProofWithPublicInputsTarget};
+
+/// Aggregates `proofs` of the circuit `circuit_data` into a single proof, by recursively verifying
+/// them in pairs along a balanced binary tree of depth `log2(proofs.len())`. A single aggregation
+/// circuit is built for each level of the tree, and reused for all the nodes of that level.
+///
+/// Each aggregation proof exposes as public inputs the hash of the public inputs of its two
+/// children, so that the root proof commits to the public inputs of all aggregated proofs.
+///
+/// Returns the root proof along with the data needed to verify it. The number of proofs must be
+/// a power of two, and at least two.
+pub fn tree_aggregate<F, C, const D: usize>(
+    proofs: Vec<ProofWithPublicInputs<F, C, D>>,
+    circuit_data: &CircuitData<F, C, D>,
+) -> Result<(ProofWithPublicInputs<F, C, D>, VerifierCircuitData<F, C, D>)>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    C::Hasher: AlgebraicHasher<F>,
+{
+    ensure!(
+        proofs.len() >= 2 && proofs.len().is_power_of_two(),
+        "Number of aggregated proofs must be a power of two, and at least two"
+    );
+
+    let config = &circuit_data.common.config;
+    let mut level = proofs;
+    let mut inner_data = circuit_data.verifier_data();
+    while level.len() > 1 {
+        let (data, targets) = aggregation_circuit(config, &inner_data);
+        level = level
+            .chunks(2)
+            .map(|pair| {
+                let mut pw = PartialWitness::new();
+                pw.set_proof_with_pis_target(&targets[0], &pair[0]);
+                pw.set_proof_with_pis_target(&targets[1], &pair[1]);
+                data.prove(pw)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        inner_data = data.verifier_data();
+    }
+
+    let root = level.pop().unwrap();
+    Ok((root, inner_data))
+}
+
+/// Builds a circuit verifying two proofs of the circuit described by `inner_data`, and exposing
+/// the hash of their public inputs.
+fn aggregation_circuit<F, C, const D: usize>(
+    config: &CircuitConfig,
+    inner_data: &VerifierCircuitData<F, C, D>,
+) -> (CircuitData<F, C, D>, [ProofWithPublicInputsTarget<D>; 2])
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    C::Hasher: AlgebraicHasher<F>,
+{
+    let mut builder = CircuitBuilder::<F, D>::new(config.clone());
+    let inner_verifier_data = builder.constant_verifier_data(&inner_data.verifier_only);
+
+    let proofs = [
+        builder.add_virtual_proof_with_pis(&inner_data.common),
+        builder.add_virtual_proof_with_pis(&inner_data.common),
+    ];
+    for proof in &proofs {
+        builder.verify_proof::<C>(proof, &inner_verifier_data, &inner_data.common);
+    }
+
+    let public_inputs = proofs
+        .iter()
+        .flat_map(|proof| proof.public_inputs.iter().copied())
+        .collect();
+    let public_inputs_hash = builder.hash_n_to_hash_no_pad::<C::InnerHasher>(public_inputs);
+    builder.register_public_inputs(&public_inputs_hash.elements);
+
+    (builder.build::<C>(), proofs)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::field::types::Field;
+    use crate::hash::hash_types::HashOut;
+    use crate::plonk::config::{Hasher, PoseidonGoldilocksConfig};
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type H = <C as GenericConfig<D>>::InnerHasher;
+
+    /// Aggregates `n` proofs of a circuit computing `x * y` from public `x` and private `y`, and
+    /// checks that the root proof verifies and commits to all of their public inputs.
+    fn test_tree_aggregate(n: usize) -> Result<()> {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let x = builder.add_virtual_public_input();
+        let y = builder.add_virtual_target();
+        let z = builder.mul(x, y);
+        builder.register_public_input(z);
+        let data = builder.build::<C>();
+
+        let proofs = (0..n)
+            .map(|i| {
+                let mut pw = PartialWitness::new();
+                pw.set_target(x, F::from_canonical_usize(i));
+                pw.set_target(y, F::from_canonical_usize(i + 1));
+                data.prove(pw)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut expected = proofs
+            .iter()
+            .map(|proof| proof.public_inputs.clone())
+            .collect::<Vec<_>>();
+        while expected.len() > 1 {
+            expected = expected
+                .chunks(2)
+                .map(|pair| {
+                    let hash: HashOut<F> = H::hash_no_pad(&pair.concat());
+                    hash.elements.to_vec()
+                })
+                .collect();
+        }
+
+        let (root, root_data) = tree_aggregate(proofs, &data)?;
+        assert_eq!(root.public_inputs, expected[0]);
+        root_data.verify(root)
+    }
+
+    #[test]
+    fn test_tree_aggregate_4() -> Result<()> {
+        test_tree_aggregate(4)
+    }
+
+    #[test]
+    fn test_tree_aggregate_8() -> Result<()> {
+        test_tree_aggregate(8)
+    }
+}
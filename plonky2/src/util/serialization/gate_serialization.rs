@@ -100,6 +100,7 @@ pub mod default {
     use crate::gates::base_sum::BaseSumGate;
     use crate::gates::constant::ConstantGate;
     use crate::gates::coset_interpolation::CosetInterpolationGate;
+    use crate::gates::dual_mul_add::DualMulAddGate;
     use crate::gates::exponentiation::ExponentiationGate;
     use crate::gates::lookup::LookupGate;
     use crate::gates::lookup_table::LookupTableGate;
@@ -132,6 +133,7 @@ pub mod default {
             BaseSumGate<2>,
             ConstantGate,
             CosetInterpolationGate<F, D>,
+            DualMulAddGate,
             ExponentiationGate<F, D>,
             LookupGate,
             LookupTableGate,
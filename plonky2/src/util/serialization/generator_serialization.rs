@@ -103,13 +103,21 @@ pub mod default {
 
     use crate::gadgets::arithmetic::EqualityGenerator;
     use crate::gadgets::arithmetic_extension::QuotientGeneratorExtension;
+    use crate::gadgets::comparison::GreaterOrEqualGenerator;
+    use crate::gadgets::legendre::LegendreSymbolGenerator;
+    use crate::gadgets::message::MessageDecodeGenerator;
+    use crate::gadgets::mux::MuxGenerator;
+    use crate::gadgets::nonnative::NonNativeMulGenerator;
     use crate::gadgets::range_check::LowHighGenerator;
-    use crate::gadgets::split_base::BaseSumGenerator;
+    use crate::gadgets::select::SelectConstantGenerator;
+    use crate::gadgets::split_base::{BaseSumGenerator, LimbRecombineGenerator};
     use crate::gadgets::split_join::{SplitGenerator, WireSplitGenerator};
+    use crate::gadgets::sqrt::SqrtGenerator;
     use crate::gates::arithmetic_base::ArithmeticBaseGenerator;
     use crate::gates::arithmetic_extension::ArithmeticExtensionGenerator;
     use crate::gates::base_sum::BaseSplitGenerator;
     use crate::gates::coset_interpolation::InterpolationGenerator;
+    use crate::gates::dual_mul_add::DualMulAddGenerator;
     use crate::gates::exponentiation::ExponentiationGenerator;
     use crate::gates::lookup::LookupGenerator;
     use crate::gates::lookup_table::LookupTableGenerator;
@@ -121,7 +129,8 @@ pub mod default {
     use crate::gates::reducing_extension::ReducingGenerator as ReducingExtensionGenerator;
     use crate::hash::hash_types::RichField;
     use crate::iop::generator::{
-        ConstantGenerator, CopyGenerator, NonzeroTestGenerator, RandomValueGenerator,
+        ConstantGenerator, CopyGenerator, InverseOfSumGenerator, NonzeroTestGenerator,
+        PublicInputsHashGenerator, RandomValueGenerator,
     };
     use crate::plonk::config::{AlgebraicHasher, GenericConfig};
     use crate::recursion::dummy_circuit::DummyProofGenerator;
@@ -159,23 +168,34 @@ pub mod default {
             BaseSumGenerator<2>,
             ConstantGenerator<F>,
             CopyGenerator,
+            DualMulAddGenerator,
             DummyProofGenerator<F, C, D>,
             EqualityGenerator,
             ExponentiationGenerator<F, D>,
+            GreaterOrEqualGenerator,
             InterpolationGenerator<F, D>,
+            InverseOfSumGenerator,
+            LegendreSymbolGenerator,
+            LimbRecombineGenerator,
             LookupGenerator,
             LookupTableGenerator,
             LowHighGenerator,
+            MessageDecodeGenerator,
             MulExtensionGenerator<F, D>,
+            MuxGenerator,
+            NonNativeMulGenerator,
             NonzeroTestGenerator,
             PoseidonGenerator<F, D>,
             PoseidonMdsGenerator<D>,
+            PublicInputsHashGenerator,
             QuotientGeneratorExtension<D>,
             RandomAccessGenerator<F, D>,
             RandomValueGenerator,
             ReducingGenerator<D>,
             ReducingExtensionGenerator<D>,
+            SelectConstantGenerator<F>,
             SplitGenerator,
+            SqrtGenerator,
             WireSplitGenerator
         }
     }
@@ -37,6 +37,8 @@ use crate::hash::hash_types::{HashOutTarget, MerkleCapTarget, RichField};
 use crate::hash::merkle_proofs::{MerkleProof, MerkleProofTarget};
 use crate::hash::merkle_tree::{MerkleCap, MerkleTree};
 use crate::iop::ext_target::ExtensionTarget;
+#[cfg(debug_assertions)]
+use crate::iop::generator::index_generators_by_watches;
 use crate::iop::generator::WitnessGeneratorRef;
 use crate::iop::target::{BoolTarget, Target};
 use crate::iop::wire::Wire;
@@ -52,6 +54,14 @@ use crate::plonk::proof::{
     ProofTarget, ProofWithPublicInputs, ProofWithPublicInputsTarget,
 };
 
+/// Magic bytes prepended to serialized [`CommonCircuitData`].
+pub const CIRCUIT_FORMAT_MAGIC: [u8; 4] = *b"PLK2";
+
+/// Version of the circuit serialization format, written after [`CIRCUIT_FORMAT_MAGIC`]. It must
+/// be bumped whenever the format changes, so that circuits serialized with an incompatible
+/// version are rejected instead of being misread.
+pub const CIRCUIT_FORMAT_VERSION: u32 = 1;
+
 /// A no_std compatible variant of `std::io::Error`
 #[derive(Debug)]
 pub struct IoError;
@@ -746,10 +756,34 @@ pub trait Read {
         })
     }
 
+    /// Reads the circuit format magic and version, failing if they don't match
+    /// [`CIRCUIT_FORMAT_MAGIC`] and [`CIRCUIT_FORMAT_VERSION`].
+    fn read_circuit_format_version(&mut self) -> IoResult<()> {
+        let mut magic = [0; CIRCUIT_FORMAT_MAGIC.len()];
+        self.read_exact(&mut magic)?;
+        if magic != CIRCUIT_FORMAT_MAGIC {
+            log::error!("attempted to deserialize circuit data without a circuit format header");
+            return Err(IoError);
+        }
+
+        let version = self.read_u32()?;
+        if version != CIRCUIT_FORMAT_VERSION {
+            log::error!(
+                "attempted to deserialize circuit data with format version {}, expected version {}",
+                version,
+                CIRCUIT_FORMAT_VERSION
+            );
+            return Err(IoError);
+        }
+
+        Ok(())
+    }
+
     fn read_common_circuit_data<F: RichField + Extendable<D>, const D: usize>(
         &mut self,
         gate_serializer: &dyn GateSerializer<F, D>,
     ) -> IoResult<CommonCircuitData<F, D>> {
+        self.read_circuit_format_version()?;
         let config = self.read_circuit_config()?;
         let fri_params = self.read_fri_params()?;
 
@@ -891,6 +925,26 @@ pub trait Read {
             lut_to_lookups.push(self.read_target_lut()?);
         }
 
+        let random_value_targets = self.read_target_vec()?;
+
+        // The watch index is stored so that loading doesn't need to rebuild it from every
+        // generator, but in debug builds we still check that it matches the generators.
+        #[cfg(debug_assertions)]
+        {
+            let num_wires = common_data.config.num_wires;
+            let degree = common_data.degree();
+            let expected = index_generators_by_watches(&generators, |t| {
+                representative_map
+                    .get(t.index(num_wires, degree))
+                    .copied()
+                    .unwrap_or(usize::MAX)
+            });
+            if expected != generator_indices_by_watches {
+                log::error!("Generator watch index doesn't match the generators");
+                return Err(IoError);
+            }
+        }
+
         Ok(ProverOnlyCircuitData {
             generators,
             generator_indices_by_watches,
@@ -903,6 +957,7 @@ pub trait Read {
             circuit_digest,
             lookup_rows,
             lut_to_lookups,
+            random_value_targets,
         })
     }
 
@@ -1762,6 +1817,12 @@ pub trait Write {
         Ok(())
     }
 
+    /// Writes the circuit format magic and version.
+    fn write_circuit_format_version(&mut self) -> IoResult<()> {
+        self.write_all(&CIRCUIT_FORMAT_MAGIC)?;
+        self.write_u32(CIRCUIT_FORMAT_VERSION)
+    }
+
     fn write_common_circuit_data<F: RichField + Extendable<D>, const D: usize>(
         &mut self,
         common_data: &CommonCircuitData<F, D>,
@@ -1783,6 +1844,7 @@ pub trait Write {
             luts,
         } = common_data;
 
+        self.write_circuit_format_version()?;
         self.write_circuit_config(config)?;
         self.write_fri_params(fri_params)?;
 
@@ -1853,6 +1915,7 @@ pub trait Write {
             circuit_digest,
             lookup_rows,
             lut_to_lookups,
+            random_value_targets,
         } = prover_only_circuit_data;
 
         self.write_usize(generators.len())?;
@@ -1903,6 +1966,8 @@ pub trait Write {
             self.write_target_lut(tlut)?;
         }
 
+        self.write_target_vec(random_value_targets)?;
+
         Ok(())
     }
 